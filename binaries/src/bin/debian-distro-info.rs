@@ -16,7 +16,7 @@ fn main() {
             flag("lts", Some('l'), "list of all LTS supported versions", None),
             flag("oldstable", Some('o'), "latest oldstable version", Some("old")),
             flag("testing", Some('t'), "current testing version", None),
-            Arg::new("alias").long("alias").help("print the alias (oldstable, stable, testing, unstable) relative to the given distribution codename"),
+            Arg::new("alias").long("alias").allow_hyphen_values(true).value_name("DIST").help("print the alias (oldstable, stable, testing, unstable) relative to the given distribution codename"),
         ],
     };
     command.main(&run)
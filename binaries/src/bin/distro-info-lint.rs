@@ -0,0 +1,92 @@
+use anyhow::{bail, format_err, Error};
+use clap::{crate_version, Arg, ArgAction, Command};
+use distro_info::clock::{self, Tz};
+use distro_info::{DebianDistroInfo, Distro, DistroInfo, UbuntuDistroInfo};
+use distro_info_binaries::lint::{self, Severity};
+
+fn parse_distro(value: &str) -> Result<Distro, Error> {
+    match value {
+        "ubuntu" => Ok(Distro::Ubuntu),
+        "debian" => Ok(Distro::Debian),
+        _ => bail!(
+            "unknown distribution `{}'; must be one of ubuntu, debian",
+            value
+        ),
+    }
+}
+
+/// Lint each of the given files, returning whether any had errors
+fn run() -> Result<bool, Error> {
+    let matches = Command::new("distro-info-lint")
+        .version(crate_version!())
+        .author("Daniel Watkins <daniel@daniel-watkins.co.uk>")
+        .about("Check distro-info-data CSV files for errors and departures from release policy")
+        .arg(
+            Arg::new("distro")
+                .long("distro")
+                .value_parser(parse_distro)
+                .value_name("distro")
+                .help("distribution the files describe (default: guessed from each file name)"),
+        )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("treat warnings as errors"),
+        )
+        .arg(
+            Arg::new("files")
+                .action(ArgAction::Append)
+                .value_name("file")
+                .help("CSV files to check (default: the system ubuntu.csv and debian.csv)"),
+        )
+        .get_matches();
+    let files: Vec<String> = match matches.get_many::<String>("files") {
+        Some(files) => files.cloned().collect(),
+        None => vec![
+            UbuntuDistroInfo::DEFAULT_CSV_PATH.to_string(),
+            DebianDistroInfo::DEFAULT_CSV_PATH.to_string(),
+        ],
+    };
+    let failing = if matches.get_flag("strict") {
+        Severity::Warning
+    } else {
+        Severity::Error
+    };
+    let today = clock::from_env(Tz::Utc)?.today();
+    let mut failed = false;
+    for path in files {
+        let distro = match matches.get_one::<Distro>("distro") {
+            Some(distro) => *distro,
+            None => lint::distro_for_path(&path).ok_or_else(|| {
+                format_err!(
+                    "cannot tell which distribution {} describes; use --distro",
+                    path
+                )
+            })?,
+        };
+        let rdr = csv::ReaderBuilder::new()
+            .flexible(true)
+            .from_path(&path)
+            .map_err(|e| format_err!("failed to read {}: {}", path, e))?;
+        for finding in lint::lint(distro, rdr, today)? {
+            println!(
+                "{}:{}: {}: {}",
+                path, finding.line, finding.severity, finding.message
+            );
+            failed |= finding.severity >= failing;
+        }
+    }
+    Ok(failed)
+}
+
+fn main() {
+    match run() {
+        Ok(false) => {}
+        Ok(true) => ::std::process::exit(1),
+        Err(ref e) => {
+            eprintln!("distro-info-lint: {}", e);
+            ::std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use std::path::PathBuf;
+
+use anyhow::Error;
+use clap::{crate_version, Arg, Command};
+use distro_info::clock::Tz;
+use distro_info::{DebianDistroInfo, DistroInfo, UbuntuDistroInfo};
+use distro_info_binaries::server::Server;
+
+fn run() -> Result<(), Error> {
+    let matches = Command::new("distro-info-server")
+        .version(crate_version!())
+        .author("Daniel Watkins <daniel@daniel-watkins.co.uk>")
+        .about("Serve distro-info queries as JSON over HTTP")
+        .arg(
+            Arg::new("listen")
+                .long("listen")
+                .default_value("127.0.0.1:8080")
+                .value_name("address")
+                .help("address and port to listen on"),
+        )
+        .arg(
+            Arg::new("ubuntu-csv")
+                .long("ubuntu-csv")
+                .default_value(UbuntuDistroInfo::DEFAULT_CSV_PATH)
+                .value_parser(clap::value_parser!(PathBuf))
+                .value_name("path")
+                .help("path to ubuntu.csv"),
+        )
+        .arg(
+            Arg::new("debian-csv")
+                .long("debian-csv")
+                .default_value(DebianDistroInfo::DEFAULT_CSV_PATH)
+                .value_parser(clap::value_parser!(PathBuf))
+                .value_name("path")
+                .help("path to debian.csv"),
+        )
+        .arg(
+            Arg::new("tz")
+                .long("tz")
+                .default_value("UTC")
+                .value_parser(|tz: &str| tz.parse::<Tz>())
+                .value_name("timezone")
+                .help("timezone for determining today's date"),
+        )
+        .get_matches();
+    let server = Server::bind(
+        matches.get_one::<String>("listen").unwrap().as_str(),
+        matches.get_one::<PathBuf>("ubuntu-csv").unwrap(),
+        matches.get_one::<PathBuf>("debian-csv").unwrap(),
+        *matches.get_one::<Tz>("tz").unwrap(),
+    )?;
+    if let Some(addr) = server.local_addr() {
+        eprintln!("distro-info-server: listening on http://{}", addr);
+    }
+    server.run()
+}
+
+fn main() {
+    if let Err(ref e) = run() {
+        eprintln!("distro-info-server: {}", e);
+        ::std::process::exit(1);
+    }
+}
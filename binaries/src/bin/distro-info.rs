@@ -0,0 +1,192 @@
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, format_err, Error};
+use chrono::NaiveDate;
+use clap::{crate_version, Arg, ArgMatches, Command};
+use distro_info::clock::{self, Tz};
+use distro_info::date_expr::DateExpr;
+use distro_info::{DebianDistroInfo, Distro, DistroInfo, UbuntuDistroInfo};
+use distro_info_binaries::convert::{self, ConvertFormat};
+use distro_info_binaries::report::{self, ReportFormat};
+
+fn date_args() -> [Arg; 2] {
+    [
+        Arg::new("date")
+            .long("date")
+            .allow_hyphen_values(true)
+            .value_parser(|date_str: &str| date_str.parse::<DateExpr>())
+            .help(
+                "date for calculating support (default: today); accepts anything \
+                 ubuntu-distro-info --date does",
+            ),
+        Arg::new("tz")
+            .long("tz")
+            .value_parser(|tz: &str| tz.parse::<Tz>())
+            .value_name("timezone")
+            .help("timezone for determining today's date (default: UTC)"),
+    ]
+}
+
+/// Resolve `--date`, looking up any `SERIES:MILESTONE` anchor in the Ubuntu data, then the Debian
+fn resolve_date(
+    matches: &ArgMatches,
+    ubuntu: &UbuntuDistroInfo,
+    debian: &DebianDistroInfo,
+) -> Result<NaiveDate, Error> {
+    let tz = matches.get_one::<Tz>("tz").copied().unwrap_or(Tz::Utc);
+    let clock = clock::from_env(tz)?;
+    match matches.get_one::<DateExpr>("date") {
+        Some(date_expr) => date_expr
+            .resolve(ubuntu, &*clock)
+            .or_else(|error| date_expr.resolve(debian, &*clock).map_err(|_| error)),
+        None => Ok(clock.today()),
+    }
+}
+
+/// Load release data from the CSV file at `path`
+fn load<T: DistroInfo>(path: &Path) -> Result<T, Error> {
+    let file =
+        File::open(path).map_err(|e| format_err!("failed to read {}: {}", path.display(), e))?;
+    T::from_csv_reader(
+        csv::ReaderBuilder::new()
+            .flexible(true)
+            .has_headers(true)
+            .from_reader(file),
+    )
+}
+
+fn convert(matches: &ArgMatches) -> Result<(), Error> {
+    let distro = matches.get_one::<String>("distro").map(String::as_str);
+    let ubuntu = match distro {
+        None | Some("ubuntu") => Some(load::<UbuntuDistroInfo>(
+            matches.get_one::<PathBuf>("ubuntu-csv").unwrap(),
+        )?),
+        _ => None,
+    };
+    let debian = match distro {
+        None | Some("debian") => Some(load::<DebianDistroInfo>(
+            matches.get_one::<PathBuf>("debian-csv").unwrap(),
+        )?),
+        _ => None,
+    };
+    let mut distros = vec![];
+    if let Some(ubuntu) = &ubuntu {
+        distros.push((Distro::Ubuntu, ubuntu.releases().as_slice()));
+    }
+    if let Some(debian) = &debian {
+        distros.push((Distro::Debian, debian.releases().as_slice()));
+    }
+    let format = *matches.get_one::<ConvertFormat>("format").unwrap();
+    match (format, matches.get_one::<PathBuf>("output")) {
+        (ConvertFormat::Sqlite, Some(output)) => convert::write_sqlite(output, &distros),
+        (ConvertFormat::Sqlite, None) => bail!("--output is required for SQLite output"),
+        (format, Some(output)) => std::fs::write(output, convert::render(format, &distros)?)
+            .map_err(|e| format_err!("failed to write {}: {}", output.display(), e)),
+        (format, None) => {
+            print!("{}", convert::render(format, &distros)?);
+            Ok(())
+        }
+    }
+}
+
+fn run() -> Result<(), Error> {
+    let matches = Command::new("distro-info")
+        .version(crate_version!())
+        .author("Daniel Watkins <daniel@daniel-watkins.co.uk>")
+        .about("Query Debian and Ubuntu release data together")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("report")
+                .about("Report the support status of an inventory of hosts")
+                .arg(
+                    Arg::new("inventory")
+                        .long("inventory")
+                        .required(true)
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .value_name("path")
+                        .help(
+                            "CSV file with host, series and (optionally) distro columns, or a \
+                             JSON array of objects with those keys if path ends in .json",
+                        ),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .default_value("text")
+                        .value_parser(ReportFormat::from_cli)
+                        .value_name("format")
+                        .help("output format (text, json)"),
+                )
+                .args(date_args()),
+        )
+        .subcommand(
+            Command::new("convert")
+                .about("Convert release data to JSON, YAML, TOML or an SQLite database")
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .required(true)
+                        .value_parser(ConvertFormat::from_cli)
+                        .value_name("format")
+                        .help("output format (json, yaml, toml, sqlite)"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .value_name("path")
+                        .help("file to write (default: standard output; required for sqlite)"),
+                )
+                .arg(
+                    Arg::new("distro")
+                        .long("distro")
+                        .value_parser(["ubuntu", "debian"])
+                        .value_name("distro")
+                        .help("convert only this distribution's data (default: both)"),
+                )
+                .arg(
+                    Arg::new("ubuntu-csv")
+                        .long("ubuntu-csv")
+                        .default_value(UbuntuDistroInfo::DEFAULT_CSV_PATH)
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .value_name("path")
+                        .help("path to ubuntu.csv"),
+                )
+                .arg(
+                    Arg::new("debian-csv")
+                        .long("debian-csv")
+                        .default_value(DebianDistroInfo::DEFAULT_CSV_PATH)
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .value_name("path")
+                        .help("path to debian.csv"),
+                ),
+        )
+        .get_matches();
+    match matches.subcommand() {
+        Some(("report", matches)) => {
+            let ubuntu = UbuntuDistroInfo::new()?;
+            let debian = DebianDistroInfo::new()?;
+            let date = resolve_date(matches, &ubuntu, &debian)?;
+            let hosts = report::read_inventory(matches.get_one::<PathBuf>("inventory").unwrap())?;
+            let groups = report::build(&hosts, &ubuntu, &debian, date)?;
+            match matches.get_one::<ReportFormat>("format").unwrap() {
+                ReportFormat::Text => print!("{}", report::render_text(&groups, date)),
+                ReportFormat::Json => println!(
+                    "{}",
+                    serde_json::to_string_pretty(&report::render_json(&groups, date))?
+                ),
+            }
+        }
+        Some(("convert", matches)) => convert(matches)?,
+        _ => unreachable!("a subcommand is required"),
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(ref e) = run() {
+        eprintln!("distro-info: {}", e);
+        ::std::process::exit(1);
+    }
+}
@@ -16,8 +16,19 @@ fn main() {
     let command = DistroInfoCommand {
         command_name: "ubuntu-distro-info",
         additional_args: vec![
-            flag("latest", Some('l'), "", None),
+            flag(
+                "latest",
+                Some('l'),
+                "latest development or supported version",
+                None,
+            ),
             flag("lts", None, "latest long term support (LTS) version", None),
+            flag(
+                "supported-esm",
+                None,
+                "list of all Ubuntu Pro supported stable versions",
+                None,
+            ),
         ],
     };
     command.main(&run)
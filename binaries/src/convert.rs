@@ -0,0 +1,239 @@
+//! Convert release data to other formats, for `distro-info convert`.
+//!
+//! JSON, YAML and TOML output is a document mapping each distro (`debian`, `ubuntu`) to its
+//! releases, as serialized by `DistroRelease`.  SQLite output is a database with a `releases`
+//! table, a `milestones` table holding each release's date-valued fields other than `created` and
+//! `release` (one row per milestone), and an `extra_fields` table for any other non-standard
+//! columns.
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{bail, Error};
+use chrono::NaiveDate;
+use distro_info::{Distro, DistroRelease, Milestone};
+use rusqlite::{params, Connection};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConvertFormat {
+    Json,
+    Yaml,
+    Toml,
+    Sqlite,
+}
+
+impl ConvertFormat {
+    pub fn from_cli(value: &str) -> Result<Self, Error> {
+        match value {
+            "json" => Ok(Self::Json),
+            "yaml" => Ok(Self::Yaml),
+            "toml" => Ok(Self::Toml),
+            "sqlite" => Ok(Self::Sqlite),
+            _ => bail!(
+                "unknown output format `{}'; must be one of json, yaml, toml, sqlite",
+                value
+            ),
+        }
+    }
+}
+
+/// The milestones stored in the `milestones` table, named as in distro-info-data
+const MILESTONES: [(&str, Milestone); 5] = [
+    ("eol", Milestone::Eol),
+    ("eol-lts", Milestone::EolLTS),
+    ("eol-elts", Milestone::EolELTS),
+    ("eol-esm", Milestone::EolESM),
+    ("eol-server", Milestone::EolServer),
+];
+
+const SCHEMA: &str = "
+CREATE TABLE releases (
+    id INTEGER PRIMARY KEY,
+    distro TEXT NOT NULL,
+    version TEXT,
+    codename TEXT NOT NULL,
+    series TEXT NOT NULL,
+    created TEXT,
+    release TEXT,
+    UNIQUE (distro, series)
+);
+CREATE TABLE milestones (
+    release_id INTEGER NOT NULL REFERENCES releases (id),
+    milestone TEXT NOT NULL,
+    date TEXT NOT NULL,
+    PRIMARY KEY (release_id, milestone)
+);
+CREATE TABLE extra_fields (
+    release_id INTEGER NOT NULL REFERENCES releases (id),
+    name TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (release_id, name)
+);
+";
+
+fn distro_key(distro: &Distro) -> String {
+    distro.to_string().to_lowercase()
+}
+
+/// Render the releases of each distro as a JSON, YAML or TOML document
+pub fn render(
+    format: ConvertFormat,
+    distros: &[(Distro, &[DistroRelease])],
+) -> Result<String, Error> {
+    let document: BTreeMap<String, &[DistroRelease]> = distros
+        .iter()
+        .map(|(distro, distro_releases)| (distro_key(distro), *distro_releases))
+        .collect();
+    Ok(match format {
+        ConvertFormat::Json => serde_json::to_string_pretty(&document)? + "\n",
+        ConvertFormat::Yaml => serde_yaml::to_string(&document)?,
+        ConvertFormat::Toml => toml::to_string(&document)?,
+        ConvertFormat::Sqlite => bail!("SQLite output must be written to a file"),
+    })
+}
+
+/// Create the tables in `conn` and insert the releases of each distro
+pub fn populate(
+    conn: &mut Connection,
+    distros: &[(Distro, &[DistroRelease])],
+) -> Result<(), Error> {
+    let tx = conn.transaction()?;
+    tx.execute_batch(SCHEMA)?;
+    for (distro, distro_releases) in distros {
+        for distro_release in distro_releases.iter() {
+            tx.execute(
+                "INSERT INTO releases (distro, version, codename, series, created, release)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    distro_key(distro),
+                    distro_release.version(),
+                    distro_release.codename(),
+                    distro_release.series(),
+                    distro_release.created().map(|date| date.to_string()),
+                    distro_release.release().map(|date| date.to_string()),
+                ],
+            )?;
+            let release_id = tx.last_insert_rowid();
+            let mut milestones: Vec<(String, NaiveDate)> = MILESTONES
+                .iter()
+                .filter_map(|(name, milestone)| {
+                    Some((name.to_string(), distro_release.milestone_date(milestone)?))
+                })
+                .collect();
+            for (name, value) in distro_release.extra_fields() {
+                match NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+                    Ok(date) => milestones.push((name.clone(), date)),
+                    Err(_) => {
+                        tx.execute(
+                            "INSERT INTO extra_fields (release_id, name, value) VALUES (?1, ?2, ?3)",
+                            params![release_id, name, value],
+                        )?;
+                    }
+                }
+            }
+            for (name, date) in milestones {
+                tx.execute(
+                    "INSERT INTO milestones (release_id, milestone, date) VALUES (?1, ?2, ?3)",
+                    params![release_id, name, date.to_string()],
+                )?;
+            }
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Write the releases of each distro to a new SQLite database at `path`
+pub fn write_sqlite(path: &Path, distros: &[(Distro, &[DistroRelease])]) -> Result<(), Error> {
+    if path.exists() {
+        bail!("{} already exists", path.display());
+    }
+    let mut conn = Connection::open(path)?;
+    populate(&mut conn, distros)
+}
+
+#[cfg(test)]
+mod tests {
+    use distro_info::{DebianDistroInfo, Distro, DistroInfo, DistroRelease, UbuntuDistroInfo};
+    use rusqlite::Connection;
+
+    use super::{populate, render, ConvertFormat};
+
+    fn releases<T: DistroInfo>(distro_info: &T, series: &[&str]) -> Vec<DistroRelease> {
+        distro_info
+            .iter()
+            .filter(|distro_release| series.contains(&distro_release.series().as_str()))
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn render_documents() {
+        let ubuntu = releases(&UbuntuDistroInfo::new().unwrap(), &["jammy"]);
+        let debian = releases(&DebianDistroInfo::new().unwrap(), &["bookworm"]);
+        let distros = [
+            (Distro::Ubuntu, ubuntu.as_slice()),
+            (Distro::Debian, debian.as_slice()),
+        ];
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render(ConvertFormat::Json, &distros).unwrap()).unwrap();
+        assert_eq!("jammy", json["ubuntu"][0]["series"]);
+        assert_eq!("2026-07-11", json["debian"][0]["eol"]);
+
+        let yaml = render(ConvertFormat::Yaml, &distros).unwrap();
+        assert!(yaml.starts_with("debian:\n- version: '12'\n  codename: Bookworm\n"));
+
+        let toml = render(ConvertFormat::Toml, &distros).unwrap();
+        assert!(toml.starts_with("[[debian]]\nversion = \"12\"\ncodename = \"Bookworm\"\n"));
+        assert!(toml.contains("[[ubuntu]]\nversion = \"22.04 LTS\"\n"));
+
+        assert!(render(ConvertFormat::Sqlite, &distros).is_err());
+    }
+
+    #[test]
+    fn sqlite_tables() {
+        let ubuntu = releases(&UbuntuDistroInfo::new().unwrap(), &["jammy", "noble"]);
+        let debian = releases(&DebianDistroInfo::new().unwrap(), &["bookworm", "sid"]);
+        let mut conn = Connection::open_in_memory().unwrap();
+        populate(
+            &mut conn,
+            &[
+                (Distro::Ubuntu, ubuntu.as_slice()),
+                (Distro::Debian, debian.as_slice()),
+            ],
+        )
+        .unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM releases", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(4, count);
+        let version: Option<String> = conn
+            .query_row(
+                "SELECT version FROM releases WHERE distro = 'debian' AND series = 'sid'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(None, version);
+        let mut statement = conn
+            .prepare(
+                "SELECT milestone, date FROM milestones JOIN releases ON release_id = id
+                 WHERE series = 'jammy' ORDER BY date, milestone",
+            )
+            .unwrap();
+        let milestones: Vec<(String, String)> = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            vec![
+                ("eol".to_string(), "2027-06-01".to_string()),
+                ("eol-server".to_string(), "2027-06-01".to_string()),
+                ("eol-esm".to_string(), "2032-04-21".to_string()),
+                ("eol-legacy".to_string(), "2034-04-25".to_string()),
+            ],
+            milestones
+        );
+    }
+}
@@ -1,14 +1,29 @@
-use anyhow::{bail, format_err, Context, Error};
-use chrono::Datelike;
+pub mod convert;
+pub mod lint;
+pub mod prometheus;
+pub mod report;
+pub mod server;
+pub mod table;
+
+use anyhow::{bail, format_err, Error};
 use chrono::NaiveDate;
-use chrono::Utc;
-use clap::{crate_version, Arg, ArgAction, ArgGroup, ArgMatches, Command};
-use distro_info::Distro;
+use clap::error::{ContextKind, ContextValue, ErrorKind};
+use clap::{crate_version, Arg, ArgAction, ArgGroup, ArgMatches, Command, Id};
+use distro_info::clock::{self, Tz};
+use distro_info::date_expr::{named_milestone_date, DateExpr};
+use distro_info::detail::ReleaseDetail;
+use distro_info::filter::Filter;
+use distro_info::overlap;
+use distro_info::overlay::Overlaid;
+use distro_info::resolve::resolve;
+use distro_info::stats::{self, Statistic};
+use distro_info::suggest::UnknownRelease;
+use distro_info::timeline::{self, TimelineFormat};
 use distro_info::Milestone;
-use distro_info::{DistroInfo, DistroRelease};
+use distro_info::{Distro, DistroInfo, DistroRelease, Selector};
+use table::{TableColumn, TableFormat};
 
-pub const OUTDATED_MSG: &str = "Distribution data outdated.
-Please check for an update for distro-info-data. See /usr/share/doc/distro-info-data/README.Debian for details.";
+pub use distro_info::{alias_for, OUTDATED_MSG};
 
 #[derive(Clone)]
 pub enum DaysMode {
@@ -16,20 +31,63 @@ pub enum DaysMode {
     Eol,
     EolLTS,
     EolELTS,
+    EolESM,
     EolServer,
     Release,
+    /// A date-valued extra column of the data, e.g. from an overlay
+    Custom(String),
 }
 
 impl DaysMode {
+    /// Every milestone `--days` accepts, other than extra columns of the data
+    pub const ALL: [DaysMode; 7] = [
+        Self::Created,
+        Self::Release,
+        Self::Eol,
+        Self::EolLTS,
+        Self::EolELTS,
+        Self::EolESM,
+        Self::EolServer,
+    ];
+
     pub fn from_cli(value: &str) -> Result<Self, Error> {
         match value {
             "created" => Ok(Self::Created),
             "eol" => Ok(Self::Eol),
             "eol-lts" => Ok(Self::EolLTS),
             "eol-elts" => Ok(Self::EolELTS),
+            "eol-esm" => Ok(Self::EolESM),
             "eol-server" => Ok(Self::EolServer),
             "release" => Ok(Self::Release),
-            _ => bail!("unknown days mode found; please report a bug"),
+            "" => bail!("unknown days mode found; please report a bug"),
+            // Whether the column exists is checked once the data is loaded
+            _ => Ok(Self::Custom(value.to_string())),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Created => "created",
+            Self::Eol => "eol",
+            Self::EolLTS => "eol-lts",
+            Self::EolELTS => "eol-elts",
+            Self::EolESM => "eol-esm",
+            Self::EolServer => "eol-server",
+            Self::Release => "release",
+            Self::Custom(name) => name,
+        }
+    }
+
+    /// The EOL milestone corresponding to this mode, if it is one
+    pub fn milestone(&self) -> Option<Milestone> {
+        match self {
+            Self::Created | Self::Release => None,
+            Self::Eol => Some(Milestone::Eol),
+            Self::EolLTS => Some(Milestone::EolLTS),
+            Self::EolELTS => Some(Milestone::EolELTS),
+            Self::EolESM => Some(Milestone::EolESM),
+            Self::EolServer => Some(Milestone::EolServer),
+            Self::Custom(name) => Some(Milestone::Custom(name.clone())),
         }
     }
 
@@ -42,11 +100,16 @@ impl DaysMode {
             DaysMode::Eol => *distro_release.eol(),
             DaysMode::EolLTS => *distro_release.eol_lts(),
             DaysMode::EolELTS => *distro_release.eol_elts(),
+            DaysMode::EolESM => *distro_release.eol_esm(),
             DaysMode::EolServer => *distro_release.eol_server(),
             DaysMode::Release => Some(distro_release.release().ok_or(format_err!(
                 "No release date found for {}",
                 &distro_release.series()
             ))?),
+            DaysMode::Custom(name) => match distro_release.extra_field(name) {
+                Some(_) => named_milestone_date(distro_release, name)?,
+                None => None,
+            },
         })
     }
 }
@@ -78,13 +141,14 @@ pub struct DistroInfoCommand {
 }
 
 impl DistroInfoCommand {
-    /// Add arguments common to both ubuntu- and debian-distro-info to `app`
-    pub fn create_command(self) -> Command {
+    /// The arguments selecting which releases to output, common and distro-specific
+    fn selector_args(&self) -> Vec<Arg> {
         let mut args = vec![
             flag("all", Some('a'), "list all known versions", None),
             flag("devel", Some('d'), "latest development version", None),
             Arg::new("series")
                 .long("series")
+                .allow_hyphen_values(true)
                 .help("series to calculate the version for"),
             flag("stable", Some('s'), "latest stable version", None),
             flag(
@@ -100,11 +164,114 @@ impl DistroInfoCommand {
                 None,
             ),
         ];
-        args.extend(self.additional_args);
+        args.extend(self.additional_args.iter().cloned());
+        args
+    }
+
+    /// The selector arguments with no upstream equivalent, which `upstream_help` and upstream's
+    /// usage errors leave out
+    fn extension_selector_args(&self) -> Vec<Arg> {
+        vec![
+            Arg::new("resolve")
+                .long("resolve")
+                .value_name("release")
+                .help(
+                    "release to calculate the version for, given as a series, codename, version, \
+                     point release or alias (e.g. stable, devel)",
+                ),
+            Arg::new("show").long("show").value_name("release").help(
+                "show every field, milestone and support phase of a release, given as for \
+                     --resolve",
+            ),
+        ]
+    }
+
+    /// The long names of the selector arguments, in alphabetical order
+    fn selector_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .selector_args()
+            .iter()
+            .map(|arg| arg.get_long().unwrap().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// The `--help` output of the upstream C implementation, covering the options it shares with
+    /// this one (the others are described by `--help-all`)
+    pub fn upstream_help(&self, distro: &Distro) -> String {
+        let milestones = match distro {
+            Distro::Ubuntu => "created, release, eol, eol-server, eol-esm",
+            Distro::Debian => "created, release, eol, eol-lts, eol-elts",
+        };
+        let mut out = format!("Usage: {} [options]\n\nOptions:\n", self.command_name);
+        help_line(&mut out, "-h  --help", "show this help message and exit");
+        help_line(
+            &mut out,
+            "    --date=DATE",
+            "date for calculating the version (default: today)",
+        );
+        help_line(
+            &mut out,
+            "-y[MILESTONE]",
+            "additionally, display days until milestone",
+        );
+        help_line(
+            &mut out,
+            "    --days=[MILESTONE]",
+            &format!("({})", milestones),
+        );
+        let mut args = self.selector_args();
+        args.sort_by_key(|arg| (arg.get_id() != "alias", arg.get_long().unwrap().to_string()));
+        args.extend([
+            flag("codename", Some('c'), "print the codename (default)", None),
+            flag("fullname", Some('f'), "print the full name", None),
+            flag("release", Some('r'), "print the release version", None),
+        ]);
+        for arg in args {
+            let short = arg
+                .get_short()
+                .map_or("  ".to_string(), |short| format!("-{}", short));
+            let mut option = format!("{}  --{}", short, arg.get_long().unwrap());
+            if arg.get_action().takes_values() {
+                let value_name = match arg.get_value_names() {
+                    Some([value_name, ..]) => value_name.to_string(),
+                    _ => arg.get_id().as_str().to_uppercase(),
+                };
+                option.push_str(&format!("={}", value_name));
+            }
+            let help = arg
+                .get_help()
+                .map(|help| help.to_string())
+                .unwrap_or_default();
+            help_line(&mut out, &option, &help);
+        }
+        out.push_str(&format!("\nSee {}(1) for more info.\n", self.command_name));
+        out
+    }
+
+    /// Add arguments common to both ubuntu- and debian-distro-info to `app`
+    pub fn create_command(self) -> Command {
+        let mut args = self.selector_args();
+        args.extend(self.extension_selector_args());
         let selectors = args.iter().map(|arg| arg.get_long().unwrap().to_string());
-        let command = Command::new(self.command_name)
+        Command::new(self.command_name)
             .version(crate_version!())
             .author("Daniel Watkins <daniel@daniel-watkins.co.uk>")
+            .disable_help_flag(true)
+            .arg(
+                Arg::new("help")
+                    .short('h')
+                    .long("help")
+                    .action(ArgAction::SetTrue)
+                    .help("show this help message and exit"),
+            )
+            .arg(
+                Arg::new("help-all")
+                    .long("help-all")
+                    .action(ArgAction::Help)
+                    .help("show help for all options, including those upstream lacks"),
+            )
             .arg(flag(
                 "codename",
                 Some('c'),
@@ -121,15 +288,43 @@ impl DistroInfoCommand {
             .arg(
                 Arg::new("date")
                     .long("date")
-                    .value_parser(|date_str: &str| {
-                        NaiveDate::parse_from_str(date_str, "%Y-%m-%d").with_context(|| {
-                            format!(
-                                "Failed to parse date '{}'; must be YYYY-MM-DD format",
-                                date_str
-                            )
-                        })
-                    })
-                    .help("date for calculating the version (default: today)"),
+                    .allow_hyphen_values(true)
+                    .value_parser(|date_str: &str| date_str.parse::<DateExpr>())
+                    .help(
+                        "date for calculating the version (default: today); accepts YYYY-MM-DD, \
+                         today, relative offsets such as +90d or -2y, and SERIES:MILESTONE",
+                    ),
+            )
+            .arg(
+                Arg::new("filter")
+                    .long("filter")
+                    .value_parser(|filter: &str| filter.parse::<Filter>())
+                    .value_name("expression")
+                    .help(
+                        "only include versions matching expression, e.g. \
+                         'lts && eol_esm > 2030-01-01 && release < noble.release'",
+                    ),
+            )
+            .arg(
+                Arg::new("overlay")
+                    .long("overlay")
+                    .action(ArgAction::Append)
+                    .value_name("path")
+                    .help(
+                        "amend the data with the CSV (or .toml) file at path; rows override fields \
+                         of the release with the same series, or add new releases (may be \
+                         repeated)",
+                    ),
+            )
+            .arg(
+                Arg::new("tz")
+                    .long("tz")
+                    .value_parser(|tz: &str| tz.parse::<Tz>())
+                    .value_name("timezone")
+                    .help(
+                        "timezone for determining today's date: UTC (default), local or an \
+                         offset such as +05:30; SOURCE_DATE_EPOCH is honoured if set",
+                    ),
             )
             .arg(
                 Arg::new("days")
@@ -141,55 +336,225 @@ impl DistroInfoCommand {
                     .value_name("milestone")
                     .help("additionally, display days until milestone"),
             )
-            .group(ArgGroup::new("selector").args(selectors).required(true))
-            .group(ArgGroup::new("output").args(["codename", "fullname", "release"]))
-            .args(args);
-        command
+            .arg(
+                Arg::new("timeline")
+                    .long("timeline")
+                    .default_missing_value("ascii")
+                    .num_args(0..=1)
+                    .value_parser(|format: &str| format.parse::<TimelineFormat>())
+                    .value_name("format")
+                    .conflicts_with_all(["codename", "fullname", "release", "days"])
+                    .help("draw a support timeline of the versions (ascii, svg, mermaid)"),
+            )
+            .arg(
+                Arg::new("table")
+                    .long("table")
+                    .default_missing_value("text")
+                    .num_args(0..=1)
+                    .value_parser(TableFormat::from_cli)
+                    .value_name("format")
+                    .conflicts_with_all(["codename", "fullname", "release", "days", "timeline"])
+                    .help("print a table of the versions (text, markdown, html)"),
+            )
+            .arg(
+                Arg::new("prometheus")
+                    .long("prometheus")
+                    .default_missing_value("-")
+                    .num_args(0..=1)
+                    .value_name("path")
+                    .conflicts_with_all([
+                        "codename", "fullname", "release", "days", "timeline", "table",
+                    ])
+                    .help(
+                        "write days-until-milestone metrics in Prometheus textfile format to \
+                         path (default: stdout)",
+                    ),
+            )
+            .arg(
+                Arg::new("overlap")
+                    .long("overlap")
+                    .default_missing_value("eol")
+                    .num_args(0..=1)
+                    .value_parser(|milestone: &str| milestone.parse::<Milestone>())
+                    .value_name("milestone")
+                    .conflicts_with_all([
+                        "codename",
+                        "fullname",
+                        "release",
+                        "days",
+                        "timeline",
+                        "table",
+                        "prometheus",
+                    ])
+                    .help(
+                        "print the periods during which successive versions are both supported \
+                         until milestone (default: eol)",
+                    ),
+            )
+            .arg(
+                flag(
+                    "stats",
+                    None,
+                    "print statistics of release cadence and support durations, in days",
+                    None,
+                )
+                .conflicts_with_all([
+                    "codename",
+                    "fullname",
+                    "release",
+                    "days",
+                    "timeline",
+                    "table",
+                    "prometheus",
+                    "overlap",
+                ]),
+            )
+            .arg(
+                Arg::new("columns")
+                    .long("columns")
+                    .value_delimiter(',')
+                    .value_parser(TableColumn::from_cli)
+                    .value_name("columns")
+                    .requires("table")
+                    .help("comma-separated columns to include in --table (default: all)"),
+            )
+            // Selections are checked by `check_selection`, after `--help` has had its chance
+            .group(ArgGroup::new("selector").args(selectors).multiple(true))
+            .group(
+                ArgGroup::new("output")
+                    .args(["codename", "fullname", "release"])
+                    .multiple(true),
+            )
+            .args(args)
     }
 
     pub fn main(self, run: &dyn Fn(DistroInfoCommand) -> Result<(), Error>) {
         let command_name = self.command_name;
         if let Err(ref e) = run(self) {
-            use std::io::Write;
+            use std::io::{IsTerminal, Write};
             let stderr = &mut ::std::io::stderr();
-            writeln!(stderr, "{}: {}", command_name, e).unwrap();
+            let message = error_message(e, stderr.is_terminal());
+            writeln!(stderr, "{}: {}", command_name, message).unwrap();
             ::std::process::exit(1);
         }
     }
 
-    pub fn run(self, distro_info: &impl DistroInfo) -> Result<(), Error> {
+    pub fn run<T: DistroInfo>(self, distro_info: &T) -> Result<(), Error> {
+        let help = self.upstream_help(distro_info.distro());
+        let selectors = self.selector_names();
         let command = self.create_command();
-        let matches = command.try_get_matches()?;
-        let date = matches
-            .get_one::<NaiveDate>("date")
-            .copied()
-            .unwrap_or_else(today);
+        let matches = match command.try_get_matches() {
+            Ok(matches) => matches,
+            Err(e) => match e.kind() {
+                ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => e.exit(),
+                _ => return Err(usage_error(&e)),
+            },
+        };
+        // As with upstream, --help takes effect whatever selections are made
+        if matches.get_flag("help") {
+            print!("{}", help);
+            return Ok(());
+        }
+        check_selection(&matches, &selectors)?;
+        let overlaid;
+        let distro_info = match matches.get_many::<String>("overlay") {
+            Some(overlays) => {
+                let overlays: Vec<&String> = overlays.collect();
+                overlaid = Overlaid::<T>::load(T::csv_path(), &overlays)?;
+                overlaid.distro_info()
+            }
+            None => distro_info,
+        };
+        if let Some(days_mode) = matches.get_one::<DaysMode>("days") {
+            check_days_mode(days_mode, distro_info)?;
+        }
+        let tz = matches.get_one::<Tz>("tz").copied().unwrap_or(Tz::Utc);
+        let clock = clock::from_env(tz)?;
+        let date = match matches.get_one::<DateExpr>("date") {
+            Some(date_expr) => date_expr.resolve(distro_info, &*clock)?,
+            None => clock.today(),
+        };
         if let Ok(Some(alias)) = matches.try_get_one::<String>("alias") {
             if !alias.chars().all(|c| c.is_lowercase()) {
-                bail!("invalid distribution codename: `{}'", alias);
+                bail!("invalid distribution codename `{}'", alias);
             };
-            let is_match = |maybe_dr: Option<&DistroRelease>| -> bool {
-                maybe_dr.map(|dr| dr.series() == alias).unwrap_or(false)
+            println!("{}", alias_for(distro_info, alias, date));
+            return Ok(());
+        };
+        let mut distro_releases = select_distro_releases(&matches, date, distro_info)?;
+        if matches.contains_id("show") {
+            for distro_release in distro_releases {
+                print!("{}", ReleaseDetail::new(distro_info, distro_release, date));
+            }
+            return Ok(());
+        }
+        if let Some(filter) = matches.get_one::<Filter>("filter") {
+            distro_releases = filter.apply(distro_releases, distro_info, date)?;
+            if distro_releases.is_empty() {
+                // An empty result is an answer to the query, not a sign of outdated data
+                return Ok(());
+            }
+        }
+        let distro_name = distro_info.distro().to_string();
+        if let Some(format) = matches.get_one::<TimelineFormat>("timeline") {
+            if distro_releases.is_empty() {
+                bail!(OUTDATED_MSG);
+            }
+            let title = format!("{} support timeline", distro_name);
+            print!(
+                "{}",
+                timeline::render(format, &title, &distro_releases, Some(date))
+            );
+            return Ok(());
+        }
+        if let Some(milestone) = matches.get_one::<Milestone>("overlap") {
+            if distro_releases.is_empty() {
+                bail!(OUTDATED_MSG);
+            }
+            for overlap in overlap::successive_overlaps(&distro_releases, milestone) {
+                let unknown = "(unknown)".to_string();
+                println!(
+                    "{} {} {} {} {}",
+                    overlap.older.series(),
+                    overlap.newer.series(),
+                    overlap.start,
+                    overlap.end.map_or(unknown.clone(), |end| end.to_string()),
+                    overlap.days().map_or(unknown, |days| days.to_string())
+                );
+            }
+            return Ok(());
+        }
+        if matches.get_flag("stats") {
+            if distro_releases.is_empty() {
+                bail!(OUTDATED_MSG);
+            }
+            print_statistics(&stats::statistics(distro_info.distro(), &distro_releases));
+            return Ok(());
+        }
+        if let Some(path) = matches.get_one::<String>("prometheus") {
+            let metrics = prometheus::render(distro_name, &distro_releases, date)?;
+            if path == "-" {
+                print!("{}", metrics);
+            } else {
+                prometheus::write(path, &metrics)?;
+            }
+            return Ok(());
+        }
+        if let Some(format) = matches.get_one::<TableFormat>("table") {
+            if distro_releases.is_empty() {
+                bail!(OUTDATED_MSG);
+            }
+            let columns = match matches.get_many::<TableColumn>("columns") {
+                Some(columns) => columns.cloned().collect(),
+                None => table::default_columns(&distro_releases),
             };
             println!(
                 "{}",
-                if is_match(distro_info.oldstable(date)) {
-                    "oldstable"
-                } else if is_match(distro_info.latest(date)) {
-                    "stable"
-                } else if is_match(distro_info.ubuntu_devel(date).last().copied()) {
-                    "testing"
-                } else if is_match(distro_info.debian_devel(date).last().copied()) {
-                    "unstable"
-                } else {
-                    alias
-                }
+                table::render(format, &columns, &distro_releases, date)
             );
             return Ok(());
-        };
-        let distro_releases = select_distro_releases(&matches, date, distro_info)?;
+        }
         let days_mode = matches.get_one::<DaysMode>("days");
-        let distro_name = distro_info.distro().to_string();
         let output_mode = if matches.get_flag("fullname") {
             OutputMode::FullName
         } else if matches.get_flag("release") {
@@ -205,6 +570,125 @@ impl DistroInfoCommand {
     }
 }
 
+/// The width of the option column of `--help` output
+const HELP_INDENT: usize = 25;
+/// The width to which `--help` output is wrapped
+const HELP_WIDTH: usize = 80;
+
+/// Append an `option` to the `--help` output in `out`, wrapping `help` beneath itself
+fn help_line(out: &mut String, option: &str, help: &str) {
+    let mut line = format!("  {:width$}", option, width = HELP_INDENT - 3);
+    for word in help.split(' ') {
+        if line.len() + 1 + word.len() >= HELP_WIDTH && line.trim().len() > option.len() {
+            out.push_str(&line);
+            out.push('\n');
+            line = " ".repeat(HELP_INDENT - 1);
+        }
+        line.push(' ');
+        line.push_str(word);
+    }
+    out.push_str(line.trim_end());
+    out.push('\n');
+}
+
+/// The first word of a clap error context value, e.g. `--series` of `--series <series>`
+fn context_arg(e: &clap::Error, kind: ContextKind) -> Option<String> {
+    match e.get(kind)? {
+        ContextValue::String(value) => value.split(' ').next().map(str::to_string),
+        ContextValue::Strings(values) => values.first().cloned(),
+        _ => None,
+    }
+}
+
+/// Check that exactly one selector and at most one output format were given, as upstream does
+///
+/// The upstream message lists only the upstream `selectors`.
+fn check_selection(matches: &ArgMatches, selectors: &[String]) -> Result<(), Error> {
+    let count = |group: &str| matches.get_many::<Id>(group).map_or(0, |ids| ids.count());
+    if count("selector") != 1 {
+        let flags: Vec<String> = selectors.iter().map(|name| format!("--{}", name)).collect();
+        bail!("You have to select exactly one of {}.", flags.join(", "));
+    }
+    if count("output") > 1 {
+        bail!("You have to select only one of --codename, --fullname, --release.");
+    }
+    Ok(())
+}
+
+/// Describe a command-line usage error as upstream does, where it has an equivalent
+fn usage_error(e: &clap::Error) -> Error {
+    let invalid_arg = context_arg(e, ContextKind::InvalidArg);
+    match e.kind() {
+        ErrorKind::UnknownArgument => match invalid_arg {
+            Some(arg) if arg.starts_with("--") => {
+                format_err!("unrecognized option '{}'", arg.split('=').next().unwrap())
+            }
+            Some(arg) => format_err!("invalid option -- '{}'", arg.trim_start_matches('-')),
+            None => format_err!("unrecognized option"),
+        },
+        ErrorKind::InvalidValue | ErrorKind::NoEquals if invalid_arg.is_some() => format_err!(
+            "option '{}' requires an argument",
+            invalid_arg.unwrap_or_default()
+        ),
+        ErrorKind::ValueValidation if invalid_arg.as_deref() == Some("--date") => {
+            match context_arg(e, ContextKind::InvalidValue) {
+                Some(value) => format_err!("invalid date `{}'", value),
+                None => format_err!("invalid date"),
+            }
+        }
+        _ => {
+            // Keep the first paragraph of clap's message, without its usage summary
+            let rendered = e.render().to_string();
+            let message: Vec<&str> = rendered
+                .lines()
+                .take_while(|line| !line.is_empty())
+                .map(str::trim)
+                .collect();
+            format_err!("{}", message.join(" ").trim_start_matches("error: "))
+        }
+    }
+}
+
+fn print_statistics(statistics: &[Statistic]) {
+    let mut rows = vec![[
+        "interval".to_string(),
+        "releases".to_string(),
+        "count".to_string(),
+        "mean".to_string(),
+        "median".to_string(),
+        "min".to_string(),
+        "max".to_string(),
+    ]];
+    for statistic in statistics {
+        let summary = &statistic.summary;
+        rows.push([
+            statistic.interval.name().to_string(),
+            statistic.category.to_string(),
+            summary.count.to_string(),
+            format!("{:.1}", summary.mean),
+            format!("{:.1}", summary.median),
+            summary.min.to_string(),
+            summary.max.to_string(),
+        ]);
+    }
+    let widths: Vec<usize> = (0..7)
+        .map(|column| rows.iter().map(|row| row[column].len()).max().unwrap())
+        .collect();
+    for row in rows {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(column, (cell, width))| match column {
+                0 | 1 => format!("{:width$}", cell, width = width),
+                // Numbers are right-aligned
+                _ => format!("{:>width$}", cell, width = width),
+            })
+            .collect();
+        println!("{}", cells.join("  ").trim_end());
+    }
+}
+
 fn determine_day_delta(current_date: NaiveDate, target_date: NaiveDate) -> i64 {
     target_date.signed_duration_since(current_date).num_days()
 }
@@ -263,6 +747,61 @@ pub fn output(
     Ok(())
 }
 
+/// Check that a custom `--days` milestone is a column of the loaded data
+///
+/// The standard milestones are always accepted, whether or not the data has dates for them.
+fn check_days_mode(days_mode: &DaysMode, distro_info: &impl DistroInfo) -> Result<(), Error> {
+    let DaysMode::Custom(name) = days_mode else {
+        return Ok(());
+    };
+    let mut columns: Vec<&str> = DaysMode::ALL.iter().map(DaysMode::name).collect();
+    for distro_release in distro_info.iter() {
+        for column in distro_release.extra_fields().keys() {
+            if !columns.contains(&column.as_str()) {
+                columns.push(column);
+            }
+        }
+    }
+    if !columns.contains(&name.as_str()) {
+        bail!(
+            "invalid milestone `{}' for option '--days'; must be one of {}",
+            name,
+            columns.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// The message to print for `error`
+///
+/// "Did you mean" suggestions are only given to interactive users, so that scripts (and the
+/// upstream test suites) see exactly upstream's message.
+fn error_message(error: &Error, interactive: bool) -> String {
+    match error.downcast_ref::<UnknownRelease>() {
+        Some(unknown) if !interactive => UnknownRelease {
+            suggestions: vec![],
+            ..unknown.clone()
+        }
+        .to_string(),
+        _ => error.to_string(),
+    }
+}
+
+/// The names of the selector flags, other than `--series`, across both binaries
+pub const SELECTORS: [&str; 11] = [
+    "all",
+    "supported",
+    "supported-esm",
+    "unsupported",
+    "devel",
+    "testing",
+    "latest",
+    "lts",
+    "elts",
+    "oldstable",
+    "stable",
+];
+
 pub fn select_distro_releases<'a>(
     matches: &ArgMatches,
     date: NaiveDate,
@@ -274,95 +813,159 @@ pub fn select_distro_releases<'a>(
             Err(_) => false,
         }
     };
-    Ok(if matches.get_flag("all") {
-        distro_info.iter().collect()
-    } else if matches.get_flag("supported") {
-        match distro_info.distro() {
-            Distro::Ubuntu => distro_info.ubuntu_supported(date),
-            Distro::Debian => distro_info.supported(date, Milestone::Eol),
-        }
-    } else if matches.get_flag("unsupported") {
-        match distro_info.distro() {
-            Distro::Ubuntu => distro_info.ubuntu_unsupported(date),
-            Distro::Debian => distro_info.unsupported(date, Milestone::Eol),
-        }
-    } else if matches.get_flag("devel") {
-        match distro_info.distro() {
-            Distro::Ubuntu => distro_info.ubuntu_devel(date),
-            Distro::Debian => distro_info.debian_devel(date),
-        }
-    } else if get_maybe_missing_flag("testing") {
-        // d-d-i --testing selection matches u-d-i --devel
-        distro_info.ubuntu_devel(date)
-    } else if get_maybe_missing_flag("latest") {
-        let devel_result = distro_info.ubuntu_devel(date);
-        if !devel_result.is_empty() {
-            vec![*devel_result.last().unwrap()]
-        } else {
-            distro_info
-                .latest(date)
-                .map(|distro_release| vec![distro_release])
-                .unwrap_or_else(Vec::new)
-        }
-    } else if matches.get_flag("lts") {
-        let lts_releases: Vec<_> = distro_info
-            .all_at(date)
-            .into_iter()
-            .filter(|distro_release| match distro_info.distro() {
-                Distro::Ubuntu => distro_release.ubuntu_is_lts(),
-                Distro::Debian => {
-                    !distro_release.supported_at(date, &Milestone::Eol)
-                        && distro_release.supported_at(date, &Milestone::EolLTS)
-                }
-            })
-            .collect();
-        match distro_info.distro() {
-            Distro::Ubuntu => match lts_releases.last() {
-                Some(release) => vec![*release],
-                None => bail!(OUTDATED_MSG),
-            },
-            Distro::Debian => lts_releases,
-        }
-    } else if get_maybe_missing_flag("elts") {
-        distro_info
-            .released(date)
-            .into_iter()
-            .filter(|distro_release| !distro_release.supported_at(date, &Milestone::EolLTS))
-            .filter(|distro_release| distro_release.supported_at(date, &Milestone::EolELTS))
-            .collect::<Vec<_>>()
-    } else if get_maybe_missing_flag("oldstable") {
-        distro_info
-            .oldstable(date)
-            .map(|distro_release| vec![distro_release])
-            .unwrap_or_else(Vec::new)
-    } else if matches.get_flag("stable") {
-        distro_info
-            .latest(date)
-            .map(|distro_release| vec![distro_release])
-            .unwrap_or_else(Vec::new)
+    let selector = if let Some(selector) = SELECTORS
+        .into_iter()
+        .find(|name| get_maybe_missing_flag(name))
+    {
+        selector.parse::<Selector>()?
     } else if matches.contains_id("series") {
         match matches.get_one::<String>("series") {
-            Some(needle_series) => {
-                if !needle_series.chars().all(|c| c.is_lowercase()) {
-                    bail!("invalid distribution series `{}'", needle_series);
-                };
-                let candidates: Vec<&DistroRelease> = distro_info
-                    .iter()
-                    .filter(|distro_release| distro_release.series() == needle_series)
-                    .collect();
-                if candidates.is_empty() {
-                    bail!("unknown distribution series `{}'", needle_series);
-                };
-                candidates
-            }
+            Some(needle_series) => Selector::Series(needle_series.to_string()),
             None => bail!("--series requires an argument; please report a bug about this error"),
         }
+    } else if let Some(identifier) = ["resolve", "show"]
+        .into_iter()
+        .find_map(|name| matches.try_get_one::<String>(name).ok().flatten())
+    {
+        return Ok(vec![resolve(distro_info, identifier, date)?]);
     } else {
         panic!("clap prevent us from reaching here; report a bug if you see this")
-    })
+    };
+    distro_info.select(&selector, date)
 }
 
-fn today() -> NaiveDate {
-    let now = Utc::now();
-    NaiveDate::from_ymd_opt(now.year(), now.month(), now.day()).unwrap()
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use distro_info::{Distro, DistroInfo, Selector, UbuntuDistroInfo};
+
+    use super::{
+        check_days_mode, check_selection, error_message, flag, usage_error, DaysMode,
+        DistroInfoCommand,
+    };
+
+    fn command() -> DistroInfoCommand {
+        DistroInfoCommand {
+            command_name: "ubuntu-distro-info",
+            additional_args: vec![
+                flag(
+                    "latest",
+                    Some('l'),
+                    "latest development or supported version",
+                    None,
+                ),
+                flag("lts", None, "latest long term support (LTS) version", None),
+                flag(
+                    "supported-esm",
+                    None,
+                    "list of all Ubuntu Pro supported stable versions",
+                    None,
+                ),
+            ],
+        }
+    }
+
+    /// The usage error for `args`, "help" if they request help, or "ok" if they are valid
+    fn error_for(args: &[&str]) -> String {
+        let command = command();
+        let selectors = command.selector_names();
+        match command
+            .create_command()
+            .try_get_matches_from(["ubuntu-distro-info"].iter().chain(args))
+        {
+            Err(e) => usage_error(&e).to_string(),
+            Ok(matches) if matches.get_flag("help") => "help".to_string(),
+            Ok(matches) => check_selection(&matches, &selectors)
+                .map_or_else(|e| e.to_string(), |_| "ok".to_string()),
+        }
+    }
+
+    #[test]
+    fn upstream_help() {
+        let help = command().upstream_help(&Distro::Ubuntu);
+        assert!(help.starts_with(
+            "Usage: ubuntu-distro-info [options]\n\nOptions:\n  \
+             -h  --help             show this help message and exit\n"
+        ));
+        assert!(help
+            .contains("\n      --days=[MILESTONE] (created, release, eol, eol-server, eol-esm)\n"));
+        assert!(help.contains("\n      --series=SERIES    series to calculate the version for\n"));
+        assert!(help.contains(
+            "\n      --supported        list of all supported stable versions\n      \
+             --supported-esm    list of all Ubuntu Pro supported stable versions\n      \
+             --unsupported      list of all unsupported stable versions\n"
+        ));
+        assert!(!help.contains("--resolve") && !help.contains("--show"));
+        assert!(help.ends_with("\n\nSee ubuntu-distro-info(1) for more info.\n"));
+        // Options upstream lacks are left to --help-all
+        assert!(!help.contains("--timeline"));
+        assert!(help.lines().all(|line| line.len() < 80));
+    }
+
+    #[test]
+    fn usage_errors() {
+        let select = "You have to select exactly one of --all, --devel, --latest, --lts, \
+                      --series, --stable, --supported, --supported-esm, --unsupported.";
+        assert_eq!(select, error_for(&[]));
+        assert_eq!(select, error_for(&["-a", "-s"]));
+        assert_eq!(
+            "You have to select only one of --codename, --fullname, --release.",
+            error_for(&["-a", "-c", "-f"])
+        );
+        assert_eq!("unrecognized option '--foo'", error_for(&["--foo=bar"]));
+        assert_eq!("invalid option -- 'x'", error_for(&["-x"]));
+        assert_eq!(
+            "invalid date `2010-02-30'",
+            error_for(&["--date=2010-02-30", "-s"])
+        );
+        assert_eq!(
+            "option '--series' requires an argument",
+            error_for(&["--series"])
+        );
+    }
+
+    #[test]
+    fn help_requests() {
+        assert_eq!("help", error_for(&["-h"]));
+        assert_eq!("help", error_for(&["-a", "-s", "--help"]));
+        // As with getopt, an option's value is never a flag
+        assert_eq!("ok", error_for(&["--series", "-h"]));
+        assert_eq!("ok", error_for(&["--series=-h"]));
+    }
+
+    #[test]
+    fn suggestions_when_interactive() {
+        let ubuntu = UbuntuDistroInfo::new().unwrap();
+        let error = ubuntu
+            .select(&Selector::Series("jamy".to_string()), NaiveDate::MIN)
+            .unwrap_err();
+        assert_eq!(
+            "unknown distribution series `jamy'; did you mean `jammy'?",
+            error_message(&error, true)
+        );
+        assert_eq!(
+            "unknown distribution series `jamy'",
+            error_message(&error, false)
+        );
+    }
+
+    #[test]
+    fn days_modes() {
+        let ubuntu = UbuntuDistroInfo::new().unwrap();
+        let check = |value: &str| {
+            check_days_mode(&DaysMode::from_cli(value).unwrap(), &ubuntu).map_err(|e| e.to_string())
+        };
+        assert!(check("eol-lts").is_ok());
+        assert!(check("eol-legacy").is_ok());
+        // Listed by upstream's ubuntu-distro-info --help
+        assert!(check("eol-esm").is_ok());
+        assert_eq!(
+            Err(
+                "invalid milestone `eoll' for option '--days'; must be one of created, release, \
+                 eol, eol-lts, eol-elts, eol-esm, eol-server, eol-legacy"
+                    .to_string()
+            ),
+            check("eoll")
+        );
+    }
 }
@@ -0,0 +1,393 @@
+//! Check distro-info-data CSV files, for `distro-info-lint`.
+//!
+//! Records are parsed with the same parser as `DistroInfo::from_csv_reader`.  Problems which make
+//! the data wrong (unparseable records, duplicate series, codenames not matching series, dates out
+//! of order) are errors; departures from release policy are warnings, as the data records what
+//! actually happened.  The policy checks are:
+//!
+//! * Ubuntu interim releases (since 13.04) reach EOL 8 to 10 months after release;
+//! * Ubuntu LTS releases (since 12.04) reach EOL 5 years after release, allowing for the date
+//!   falling up to two months after the fifth anniversary;
+//! * Ubuntu versions match their release year and month;
+//! * Debian releases (since 6.0) which have reached EOL have an `eol-lts` date.
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Read;
+
+use anyhow::Error;
+use chrono::{Datelike, Months, NaiveDate};
+use distro_info::{parse_records, Distro, DistroRelease};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// A problem found on a line of a data file
+#[derive(Clone, Debug, PartialEq)]
+pub struct Finding {
+    pub line: u64,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Finding {
+    fn error(line: u64, message: String) -> Self {
+        Self {
+            line,
+            severity: Severity::Error,
+            message,
+        }
+    }
+
+    fn warning(line: u64, message: String) -> Self {
+        Self {
+            line,
+            severity: Severity::Warning,
+            message,
+        }
+    }
+}
+
+/// Guess the distro whose data is in the file at `path` from its name
+pub fn distro_for_path(path: &str) -> Option<Distro> {
+    let name = path.rsplit('/').next().unwrap_or(path).to_lowercase();
+    if name.contains("ubuntu") {
+        Some(Distro::Ubuntu)
+    } else if name.contains("debian") {
+        Some(Distro::Debian)
+    } else {
+        None
+    }
+}
+
+/// Check the records read from `rdr` as `distro` data, as of `today`
+///
+/// Findings are returned in line order.
+pub fn lint<R: Read>(
+    distro: Distro,
+    rdr: csv::Reader<R>,
+    today: NaiveDate,
+) -> Result<Vec<Finding>, Error> {
+    let mut findings = vec![];
+    let mut first_lines: HashMap<String, u64> = HashMap::new();
+    for (line, distro_release) in parse_records(rdr)? {
+        let distro_release = match distro_release {
+            Ok(distro_release) => distro_release,
+            Err(e) => {
+                findings.push(Finding::error(
+                    line,
+                    format!("failed to parse record: {}", e),
+                ));
+                continue;
+            }
+        };
+        let series = distro_release.series().clone();
+        match first_lines.get(&series) {
+            Some(first_line) => findings.push(Finding::error(
+                line,
+                format!(
+                    "duplicate series `{}' (first defined on line {})",
+                    series, first_line
+                ),
+            )),
+            None => {
+                first_lines.insert(series, line);
+            }
+        }
+        let mut messages = vec![];
+        check_series(&distro_release, &mut messages);
+        check_date_order(&distro_release, &mut messages);
+        findings.extend(
+            messages
+                .into_iter()
+                .map(|message| Finding::error(line, message)),
+        );
+        let mut messages = vec![];
+        match distro {
+            Distro::Ubuntu => check_ubuntu_policy(&distro_release, &mut messages),
+            Distro::Debian => check_debian_policy(&distro_release, today, &mut messages),
+        }
+        findings.extend(
+            messages
+                .into_iter()
+                .map(|message| Finding::warning(line, message)),
+        );
+    }
+    Ok(findings)
+}
+
+/// The series should be the first word of the codename, lowercased
+fn check_series(distro_release: &DistroRelease, messages: &mut Vec<String>) {
+    let codename = distro_release.codename();
+    let series = distro_release.series();
+    if series.is_empty() {
+        messages.push("empty series".to_string());
+        return;
+    }
+    let expected = codename
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    if *series != expected {
+        messages.push(format!(
+            "series `{}' does not match codename `{}' (expected `{}')",
+            series, codename, expected
+        ));
+    }
+}
+
+/// Each milestone should fall on or after the ones preceding it
+fn check_date_order(distro_release: &DistroRelease, messages: &mut Vec<String>) {
+    let mut check = |earlier: (&str, &Option<NaiveDate>), later: (&str, &Option<NaiveDate>)| {
+        if let (Some(earlier_date), Some(later_date)) = (earlier.1, later.1) {
+            if later_date < earlier_date {
+                messages.push(format!(
+                    "{} ({}) is before {} ({})",
+                    later.0, later_date, earlier.0, earlier_date
+                ));
+            }
+        }
+    };
+    let created = ("created", distro_release.created());
+    let release = ("release", distro_release.release());
+    let eol = ("eol", distro_release.eol());
+    let eol_lts = ("eol-lts", distro_release.eol_lts());
+    check(created, release);
+    check(release, eol);
+    check(eol, eol_lts);
+    check(eol, ("eol-server", distro_release.eol_server()));
+    check(eol, ("eol-esm", distro_release.eol_esm()));
+    check(eol_lts, ("eol-elts", distro_release.eol_elts()));
+}
+
+/// Parse an Ubuntu version (e.g. `22.04 LTS`) into its year and month
+fn ubuntu_version(distro_release: &DistroRelease) -> Option<(u32, u32)> {
+    let version = distro_release.version().as_ref()?;
+    let (year, month) = version.split_whitespace().next()?.split_once('.')?;
+    Some((year.parse().ok()?, month.parse().ok()?))
+}
+
+fn check_ubuntu_policy(distro_release: &DistroRelease, messages: &mut Vec<String>) {
+    let version = match ubuntu_version(distro_release) {
+        Some(version) => version,
+        None => {
+            messages.push(format!(
+                "version `{}' is not of the form YY.MM",
+                distro_release.version().as_deref().unwrap_or_default()
+            ));
+            return;
+        }
+    };
+    let release = match distro_release.release() {
+        Some(release) => *release,
+        None => return,
+    };
+    if version != (release.year() as u32 % 100, release.month()) {
+        messages.push(format!(
+            "version `{}' does not match release date {}",
+            distro_release.version().as_deref().unwrap_or_default(),
+            release
+        ));
+    }
+    let eol = match distro_release.eol() {
+        Some(eol) => *eol,
+        None => return,
+    };
+    let (earliest, latest, policy) = if distro_release.ubuntu_is_lts() {
+        if version < (12, 4) {
+            return;
+        }
+        (
+            release + Months::new(60),
+            release + Months::new(62),
+            "5 years",
+        )
+    } else {
+        if version < (13, 4) {
+            return;
+        }
+        (
+            release + Months::new(8),
+            release + Months::new(10),
+            "about 9 months",
+        )
+    };
+    if eol < earliest || eol > latest {
+        messages.push(format!(
+            "supported for {} days ({} to {}); policy is {}",
+            eol.signed_duration_since(release).num_days(),
+            release,
+            eol,
+            policy
+        ));
+    }
+}
+
+fn check_debian_policy(
+    distro_release: &DistroRelease,
+    today: NaiveDate,
+    messages: &mut Vec<String>,
+) {
+    let major = distro_release
+        .version()
+        .as_ref()
+        .and_then(|version| version.split('.').next()?.parse::<u32>().ok());
+    let is_eol = distro_release.eol().is_some_and(|eol| eol < today);
+    if major.is_some_and(|major| major >= 6) && is_eol && distro_release.eol_lts().is_none() {
+        messages.push(format!(
+            "{} has reached eol but has no eol-lts date",
+            distro_release.series()
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use distro_info::Distro;
+
+    use super::{distro_for_path, lint, Finding, Severity};
+
+    fn lint_str(distro: Distro, content: &str) -> Vec<(u64, Severity, String)> {
+        let rdr = csv::ReaderBuilder::new()
+            .flexible(true)
+            .from_reader(content.as_bytes());
+        let today = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        lint(distro, rdr, today)
+            .unwrap()
+            .into_iter()
+            .map(
+                |Finding {
+                     line,
+                     severity,
+                     message,
+                 }| (line, severity, message),
+            )
+            .collect()
+    }
+
+    #[test]
+    fn system_data_has_no_errors() {
+        for (distro, path) in [
+            (Distro::Ubuntu, "/usr/share/distro-info/ubuntu.csv"),
+            (Distro::Debian, "/usr/share/distro-info/debian.csv"),
+        ] {
+            let rdr = csv::ReaderBuilder::new()
+                .flexible(true)
+                .from_path(path)
+                .unwrap();
+            let today = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+            assert_eq!(Vec::<Finding>::new(), lint(distro, rdr, today).unwrap());
+        }
+    }
+
+    #[test]
+    fn structural_errors() {
+        let findings = lint_str(
+            Distro::Debian,
+            "version,codename,series,created,release,eol\n\
+             12,Bookworm,bookworm,2021-08-14,2023-06-10,2026-07-11\n\
+             13,Trixie,bookworm,2023-06-10,2025-08-09\n\
+             14,Forky,forky,2025-08-09,not-a-date\n\
+             15,Duke,duke,2027-08-09,2027-01-01\n",
+        );
+        let errors: Vec<_> = findings
+            .iter()
+            .filter(|(_, severity, _)| *severity == Severity::Error)
+            .map(|(line, _, message)| (*line, message.as_str()))
+            .collect();
+        assert_eq!(
+            vec![
+                (3, "duplicate series `bookworm' (first defined on line 2)"),
+                (
+                    3,
+                    "series `bookworm' does not match codename `Trixie' (expected `trixie')"
+                ),
+                (
+                    4,
+                    "failed to parse record: input contains invalid characters"
+                ),
+                (5, "release (2027-01-01) is before created (2027-08-09)"),
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn policy_warnings() {
+        let findings = lint_str(
+            Distro::Ubuntu,
+            "version,codename,series,created,release,eol\n\
+             22.04 LTS,Jammy Jellyfish,jammy,2021-10-14,2022-04-21,2025-04-21\n\
+             22.10,Kinetic Kudu,kinetic,2022-04-21,2022-10-20,2024-04-20\n\
+             23.05,Lunar Lobster,lunar,2022-10-20,2023-04-20,2024-01-25\n\
+             12.10,Quantal Quetzal,quantal,2012-04-26,2012-10-18,2014-05-16\n\
+             13.1,Raring Ringtail,raring,2012-10-18,2013-04-25,2014-01-27\n",
+        );
+        assert_eq!(
+            vec![
+                (
+                    2,
+                    Severity::Warning,
+                    "supported for 1096 days (2022-04-21 to 2025-04-21); policy is 5 years"
+                        .to_string()
+                ),
+                (
+                    3,
+                    Severity::Warning,
+                    "supported for 548 days (2022-10-20 to 2024-04-20); policy is about 9 months"
+                        .to_string()
+                ),
+                (
+                    4,
+                    Severity::Warning,
+                    "version `23.05' does not match release date 2023-04-20".to_string()
+                ),
+                (
+                    6,
+                    Severity::Warning,
+                    "version `13.1' does not match release date 2013-04-25".to_string()
+                ),
+            ],
+            findings
+        );
+
+        let findings = lint_str(
+            Distro::Debian,
+            "version,codename,series,created,release,eol,eol-lts\n\
+             5.0,Lenny,lenny,2007-04-08,2009-02-14,2012-02-06\n\
+             6.0,Squeeze,squeeze,2009-02-14,2011-02-06,2014-05-31\n\
+             13,Trixie,trixie,2023-06-10,2025-08-09,2028-08-09\n",
+        );
+        assert_eq!(
+            vec![(
+                3,
+                Severity::Warning,
+                "squeeze has reached eol but has no eol-lts date".to_string()
+            )],
+            findings
+        );
+    }
+
+    #[test]
+    fn distros_for_paths() {
+        assert_eq!(
+            Some(Distro::Ubuntu),
+            distro_for_path("/usr/share/distro-info/ubuntu.csv")
+        );
+        assert_eq!(Some(Distro::Debian), distro_for_path("debian-amended.csv"));
+        assert_eq!(None, distro_for_path("/srv/debian/releases.csv"));
+    }
+}
@@ -0,0 +1,147 @@
+use std::fmt::Write as _;
+use std::fs;
+
+use anyhow::{Context, Error};
+use chrono::NaiveDate;
+use distro_info::DistroRelease;
+
+use crate::DaysMode;
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Render metrics for `distro_releases` at `date` in the Prometheus text exposition format
+///
+/// `distro_release_days_until` is emitted for every milestone a release has a date for (as used by
+/// `--days`), and `distro_release_supported` for every EOL milestone.
+pub fn render(
+    distro_name: &str,
+    distro_releases: &[&DistroRelease],
+    date: NaiveDate,
+) -> Result<String, Error> {
+    let distro = escape_label(&distro_name.to_lowercase());
+    let labels = |distro_release: &DistroRelease, days_mode: &DaysMode| {
+        format!(
+            "distro=\"{}\",series=\"{}\",milestone=\"{}\"",
+            distro,
+            escape_label(distro_release.series()),
+            days_mode.name()
+        )
+    };
+    let mut out = String::new();
+    writeln!(
+        out,
+        "# HELP distro_release_days_until Days until a release reaches a milestone (negative once \
+         passed)"
+    )?;
+    writeln!(out, "# TYPE distro_release_days_until gauge")?;
+    for distro_release in distro_releases {
+        for days_mode in DaysMode::ALL.iter() {
+            let milestone_date =
+                distro_info::date_expr::named_milestone_date(distro_release, days_mode.name())?;
+            if let Some(milestone_date) = milestone_date {
+                writeln!(
+                    out,
+                    "distro_release_days_until{{{}}} {}",
+                    labels(distro_release, days_mode),
+                    milestone_date.signed_duration_since(date).num_days()
+                )?;
+            }
+        }
+    }
+    writeln!(
+        out,
+        "# HELP distro_release_supported Whether a release is released and supported, per an EOL \
+         milestone"
+    )?;
+    writeln!(out, "# TYPE distro_release_supported gauge")?;
+    for distro_release in distro_releases {
+        for days_mode in DaysMode::ALL.iter() {
+            let milestone = match days_mode.milestone() {
+                Some(milestone) => milestone,
+                None => continue,
+            };
+            // A missing EOL date means the release is supported, but other missing milestones
+            // mean that phase of support doesn't exist
+            if distro_release.milestone_date(&milestone).is_none()
+                && !matches!(days_mode, DaysMode::Eol)
+            {
+                continue;
+            }
+            let supported =
+                distro_release.released_at(date) && distro_release.supported_at(date, &milestone);
+            writeln!(
+                out,
+                "distro_release_supported{{{}}} {}",
+                labels(distro_release, days_mode),
+                u8::from(supported)
+            )?;
+        }
+    }
+    Ok(out)
+}
+
+/// Write `metrics` to `path`, via a temporary file so that collectors never see a partial file
+pub fn write(path: &str, metrics: &str) -> Result<(), Error> {
+    let tmp_path = format!("{}.tmp", path);
+    fs::write(&tmp_path, metrics).with_context(|| format!("failed to write {}", tmp_path))?;
+    fs::rename(&tmp_path, path)
+        .with_context(|| format!("failed to rename {} to {}", tmp_path, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use distro_info::{DistroInfo, UbuntuDistroInfo};
+
+    use super::{escape_label, render};
+
+    const CSV: &str = "version,codename,series,created,release,eol,eol-server,eol-esm
+22.04 LTS,Jammy Jellyfish,jammy,2021-10-14,2022-04-21,2027-06-01,2027-06-01,2032-04-21
+24.10,Oracular Oriole,\"or\"\"a\\cular\",2024-04-25,2024-10-10,2025-07-10
+";
+
+    #[test]
+    fn exposition_format() {
+        let distro_info = UbuntuDistroInfo::from_csv_reader(
+            csv::ReaderBuilder::new()
+                .flexible(true)
+                .from_reader(CSV.as_bytes()),
+        )
+        .unwrap();
+        let distro_releases: Vec<_> = distro_info.iter().collect();
+        let date = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        assert_eq!(
+            r#"# HELP distro_release_days_until Days until a release reaches a milestone (negative once passed)
+# TYPE distro_release_days_until gauge
+distro_release_days_until{distro="ubuntu",series="jammy",milestone="created"} -961
+distro_release_days_until{distro="ubuntu",series="jammy",milestone="release"} -772
+distro_release_days_until{distro="ubuntu",series="jammy",milestone="eol"} 1095
+distro_release_days_until{distro="ubuntu",series="jammy",milestone="eol-esm"} 2881
+distro_release_days_until{distro="ubuntu",series="jammy",milestone="eol-server"} 1095
+distro_release_days_until{distro="ubuntu",series="or\"a\\cular",milestone="created"} -37
+distro_release_days_until{distro="ubuntu",series="or\"a\\cular",milestone="release"} 131
+distro_release_days_until{distro="ubuntu",series="or\"a\\cular",milestone="eol"} 404
+# HELP distro_release_supported Whether a release is released and supported, per an EOL milestone
+# TYPE distro_release_supported gauge
+distro_release_supported{distro="ubuntu",series="jammy",milestone="eol"} 1
+distro_release_supported{distro="ubuntu",series="jammy",milestone="eol-esm"} 1
+distro_release_supported{distro="ubuntu",series="jammy",milestone="eol-server"} 1
+distro_release_supported{distro="ubuntu",series="or\"a\\cular",milestone="eol"} 0
+"#,
+            render("Ubuntu", &distro_releases, date).unwrap()
+        );
+    }
+
+    #[test]
+    fn label_escaping() {
+        assert_eq!(
+            r#"Jammy \"JJ\" \\ Jellyfish\n"#,
+            escape_label("Jammy \"JJ\" \\ Jellyfish\n")
+        );
+    }
+}
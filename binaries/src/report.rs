@@ -0,0 +1,413 @@
+//! Join an inventory of hosts against the release data, for `distro-info report`.
+//!
+//! Inventories are CSV files with `host` and `series` columns (and optionally `distro`, which is
+//! otherwise inferred from the series), or JSON arrays of objects with the same keys.
+//!
+//! Hosts are grouped by the support phase of their release (see `DistroRelease::phase_at`), and
+//! their next milestone is chosen from `MILESTONES`.  Extra milestones in the data, such as
+//! Ubuntu's `eol-legacy`, are ignored for both: a release past its last standard milestone is EOL
+//! even if it has a later extra one.
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, format_err, Error};
+use chrono::NaiveDate;
+use distro_info::date_expr::named_milestone_date;
+use distro_info::{DebianDistroInfo, DistroInfo, DistroRelease, Phase, UbuntuDistroInfo};
+use serde_json::{json, Value};
+
+/// The groups of a report, from most to least at risk
+const PHASES: [&str; 7] = [
+    "EOL",
+    "ESM",
+    "ELTS",
+    "LTS",
+    "supported",
+    "unreleased",
+    "unknown",
+];
+
+/// The milestones considered when finding a host's next milestone
+const MILESTONES: [&str; 6] = [
+    "release",
+    "eol",
+    "eol-server",
+    "eol-lts",
+    "eol-elts",
+    "eol-esm",
+];
+
+#[derive(Clone)]
+pub enum ReportFormat {
+    Text,
+    Json,
+}
+
+impl ReportFormat {
+    pub fn from_cli(value: &str) -> Result<Self, Error> {
+        match value {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => bail!(
+                "unknown report format `{}'; must be one of text, json",
+                value
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Host {
+    pub name: String,
+    pub distro: Option<String>,
+    pub series: String,
+}
+
+/// Read an inventory from a CSV file, or a JSON file if `path` ends in `.json`
+pub fn read_inventory(path: &Path) -> Result<Vec<Host>, Error> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format_err!("failed to read {}: {}", path.display(), e))?;
+    if path
+        .extension()
+        .is_some_and(|extension| extension == "json")
+    {
+        hosts_from_json(&content)
+    } else {
+        hosts_from_csv(&content)
+    }
+    .map_err(|e| format_err!("invalid inventory {}: {}", path.display(), e))
+}
+
+fn hosts_from_csv(content: &str) -> Result<Vec<Host>, Error> {
+    let mut rdr = csv::ReaderBuilder::new().from_reader(content.as_bytes());
+    let columns = rdr.headers()?.clone();
+    let column = |name: &str| columns.iter().position(|header| header == name);
+    let host_column = column("host").ok_or_else(|| format_err!("no `host' column"))?;
+    let series_column = column("series").ok_or_else(|| format_err!("no `series' column"))?;
+    let distro_column = column("distro");
+    let records = rdr.records().collect::<Result<Vec<_>, _>>()?;
+    let mut hosts = vec![];
+    for (index, record) in records.iter().enumerate() {
+        // The csv crate reads an unterminated quoted field to the end of the input without
+        // complaint, but the raw record then has an odd number of quotes
+        let start = record
+            .position()
+            .map_or(0, |position| position.byte() as usize);
+        let end = records
+            .get(index + 1)
+            .and_then(|next| next.position())
+            .map_or(content.len(), |position| position.byte() as usize);
+        if content[start..end].matches('"').count() % 2 == 1 {
+            bail!(
+                "line {}: unterminated quoted field",
+                record.position().map_or(0, |position| position.line())
+            );
+        }
+        let field = |index: usize| record.get(index).unwrap_or_default().trim().to_string();
+        hosts.push(Host {
+            name: field(host_column),
+            distro: distro_column.map(field).filter(|distro| !distro.is_empty()),
+            series: field(series_column),
+        });
+    }
+    Ok(hosts)
+}
+
+fn hosts_from_json(content: &str) -> Result<Vec<Host>, Error> {
+    let value: Value = serde_json::from_str(content)?;
+    let entries = value
+        .as_array()
+        .ok_or_else(|| format_err!("expected an array of hosts"))?;
+    entries
+        .iter()
+        .map(|entry| {
+            let field = |name: &str| entry.get(name).and_then(Value::as_str);
+            Ok(Host {
+                name: field("host")
+                    .ok_or_else(|| format_err!("host without a `host' key: {}", entry))?
+                    .to_string(),
+                distro: field("distro").map(str::to_string),
+                series: field("series")
+                    .ok_or_else(|| format_err!("host without a `series' key: {}", entry))?
+                    .to_string(),
+            })
+        })
+        .collect()
+}
+
+/// A host's release and where it is in its lifecycle
+pub struct HostStatus<'a> {
+    pub host: &'a Host,
+    pub distro_release: Option<&'a DistroRelease>,
+    pub phase: &'static str,
+    pub next_milestone: Option<(&'static str, NaiveDate)>,
+}
+
+impl HostStatus<'_> {
+    pub fn days_to_next_milestone(&self, date: NaiveDate) -> Option<i64> {
+        self.next_milestone
+            .map(|(_, milestone_date)| milestone_date.signed_duration_since(date).num_days())
+    }
+}
+
+pub struct ReportGroup<'a> {
+    pub phase: &'static str,
+    pub hosts: Vec<HostStatus<'a>>,
+}
+
+fn find_release<'a>(
+    host: &Host,
+    ubuntu: &'a UbuntuDistroInfo,
+    debian: &'a DebianDistroInfo,
+) -> Result<Option<&'a DistroRelease>, Error> {
+    let find = |mut releases: std::slice::Iter<'a, DistroRelease>| {
+        releases.find(|distro_release| distro_release.series() == &host.series)
+    };
+    Ok(
+        match host.distro.as_deref().map(str::to_lowercase).as_deref() {
+            Some("ubuntu") => find(ubuntu.iter()),
+            Some("debian") => find(debian.iter()),
+            Some(distro) => bail!("unknown distribution `{}' for host {}", distro, host.name),
+            None => find(ubuntu.iter()).or_else(|| find(debian.iter())),
+        },
+    )
+}
+
+fn phase_name(distro_release: &DistroRelease, date: NaiveDate) -> &'static str {
+    match distro_release.phase_at(date) {
+        Some(Phase::Standard) => "supported",
+        Some(phase) => phase.name(),
+        None if !distro_release.released_at(date) => "unreleased",
+        None => "EOL",
+    }
+}
+
+fn next_milestone(
+    distro_release: &DistroRelease,
+    date: NaiveDate,
+) -> Result<Option<(&'static str, NaiveDate)>, Error> {
+    let mut next: Option<(&'static str, NaiveDate)> = None;
+    for name in MILESTONES {
+        if let Some(milestone_date) = named_milestone_date(distro_release, name)? {
+            if milestone_date >= date && next.is_none_or(|(_, next)| milestone_date < next) {
+                next = Some((name, milestone_date));
+            }
+        }
+    }
+    Ok(next)
+}
+
+/// Group `hosts` by the phase of their release at `date`, each group sorted by the days until the
+/// next milestone (soonest first, with hosts without one last)
+pub fn build<'a>(
+    hosts: &'a [Host],
+    ubuntu: &'a UbuntuDistroInfo,
+    debian: &'a DebianDistroInfo,
+    date: NaiveDate,
+) -> Result<Vec<ReportGroup<'a>>, Error> {
+    let mut statuses = vec![];
+    for host in hosts {
+        let distro_release = find_release(host, ubuntu, debian)?;
+        statuses.push(match distro_release {
+            Some(distro_release) => HostStatus {
+                host,
+                distro_release: Some(distro_release),
+                phase: phase_name(distro_release, date),
+                next_milestone: next_milestone(distro_release, date)?,
+            },
+            None => HostStatus {
+                host,
+                distro_release: None,
+                phase: "unknown",
+                next_milestone: None,
+            },
+        });
+    }
+    statuses.sort_by_key(|status| {
+        (
+            status.next_milestone.is_none(),
+            status
+                .next_milestone
+                .map(|(_, milestone_date)| milestone_date),
+            status.host.name.clone(),
+        )
+    });
+    let mut groups: Vec<ReportGroup> = PHASES
+        .iter()
+        .map(|phase| ReportGroup {
+            phase,
+            hosts: vec![],
+        })
+        .collect();
+    for status in statuses {
+        let group = groups.iter_mut().find(|group| group.phase == status.phase);
+        group.unwrap().hosts.push(status);
+    }
+    groups.retain(|group| !group.hosts.is_empty());
+    Ok(groups)
+}
+
+fn distro_series(status: &HostStatus) -> String {
+    match status.distro_release {
+        Some(distro_release) => format!(
+            "{}/{}",
+            distro_release
+                .distro()
+                .map_or("unknown", |distro| distro.to_string())
+                .to_lowercase(),
+            distro_release.series()
+        ),
+        None => status.host.series.clone(),
+    }
+}
+
+pub fn render_text(groups: &[ReportGroup], date: NaiveDate) -> String {
+    let mut out = String::new();
+    for group in groups {
+        let count = group.hosts.len();
+        writeln!(
+            out,
+            "{} ({} host{})",
+            group.phase,
+            count,
+            if count == 1 { "" } else { "s" }
+        )
+        .unwrap();
+        let host_width = group.hosts.iter().map(|s| s.host.name.len()).max().unwrap();
+        let rows: Vec<_> = group.hosts.iter().map(distro_series).collect();
+        let series_width = rows.iter().map(String::len).max().unwrap();
+        for (status, series) in group.hosts.iter().zip(rows) {
+            let next = match (status.next_milestone, status.days_to_next_milestone(date)) {
+                (Some((name, milestone_date)), Some(days)) => {
+                    format!("{} {} ({} days)", name, milestone_date, days)
+                }
+                _ => "-".to_string(),
+            };
+            writeln!(
+                out,
+                "  {:host_width$}  {:series_width$}  {}",
+                status.host.name, series, next
+            )
+            .unwrap();
+        }
+    }
+    out
+}
+
+pub fn render_json(groups: &[ReportGroup], date: NaiveDate) -> Value {
+    json!({
+        "date": date.to_string(),
+        "groups": groups
+            .iter()
+            .map(|group| json!({
+                "phase": group.phase,
+                "hosts": group.hosts.iter().map(|status| json!({
+                    "host": status.host.name,
+                    "distro": status.distro_release
+                        .and_then(|distro_release| *distro_release.distro())
+                        .map(|distro| distro.to_string().to_lowercase()),
+                    "series": status.host.series,
+                    "next_milestone": status.next_milestone.map(|(name, _)| name),
+                    "next_milestone_date": status.next_milestone
+                        .map(|(_, milestone_date)| milestone_date.to_string()),
+                    "days": status.days_to_next_milestone(date),
+                })).collect::<Vec<_>>(),
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use distro_info::{DebianDistroInfo, DistroInfo, UbuntuDistroInfo};
+
+    use super::{build, hosts_from_csv, hosts_from_json, render_json, render_text, Host};
+
+    #[test]
+    fn read_inventories() {
+        let expected = vec![
+            Host {
+                name: "web1".to_string(),
+                distro: Some("ubuntu".to_string()),
+                series: "jammy".to_string(),
+            },
+            Host {
+                name: "db1".to_string(),
+                distro: None,
+                series: "bookworm".to_string(),
+            },
+        ];
+        assert_eq!(
+            expected,
+            hosts_from_csv("host,distro,series\nweb1,ubuntu,jammy\ndb1,,bookworm\n").unwrap()
+        );
+        assert_eq!(
+            expected,
+            hosts_from_json(
+                r#"[{"host": "web1", "distro": "ubuntu", "series": "jammy"},
+                    {"host": "db1", "series": "bookworm"}]"#
+            )
+            .unwrap()
+        );
+        assert!(hosts_from_csv("name,series\nweb1,jammy\n").is_err());
+        assert_eq!(
+            "line 3: unterminated quoted field",
+            hosts_from_csv("host,series\nweb1,\"jammy\"\"\"\nweb2,\"ja\n")
+                .unwrap_err()
+                .to_string()
+        );
+        assert!(hosts_from_json(r#"[{"host": "web1"}]"#).is_err());
+    }
+
+    #[test]
+    fn report_groups() {
+        let ubuntu = UbuntuDistroInfo::new().unwrap();
+        let debian = DebianDistroInfo::new().unwrap();
+        let hosts = hosts_from_csv(
+            "host,series\nweb1,jammy\nweb2,noble\nold1,xenial\nold2,warty\ndb1,bookworm\n\
+             mystery,nonexistent\n",
+        )
+        .unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let groups = build(&hosts, &ubuntu, &debian, date).unwrap();
+        let summary: Vec<_> = groups
+            .iter()
+            .map(|group| {
+                (
+                    group.phase,
+                    group
+                        .hosts
+                        .iter()
+                        .map(|status| status.host.name.as_str())
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("EOL", vec!["old2"]),
+                ("ESM", vec!["old1"]),
+                // Sorted by days until the next milestone
+                ("supported", vec!["db1", "web1", "web2"]),
+                ("unknown", vec!["mystery"]),
+            ],
+            summary
+        );
+
+        let text = render_text(&groups, date);
+        assert!(text.starts_with("EOL (1 host)\n  old2  ubuntu/warty  -\n"));
+        assert!(text.contains("  db1   debian/bookworm  eol 2026-07-11 (556 days)\n"));
+        let json = render_json(&groups, date);
+        assert_eq!("2025-01-01", json["date"]);
+        assert_eq!("debian", json["groups"][2]["hosts"][0]["distro"]);
+        assert_eq!(556, json["groups"][2]["hosts"][0]["days"]);
+
+        // Extra milestones (xenial's eol-legacy) don't count towards the phase
+        let date = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        let groups = build(&hosts[2..3], &ubuntu, &debian, date).unwrap();
+        assert_eq!("EOL", groups[0].phase);
+        assert_eq!(None, groups[0].hosts[0].next_milestone);
+    }
+}
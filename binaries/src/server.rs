@@ -0,0 +1,407 @@
+//! A small HTTP server answering distro-info queries with JSON.
+//!
+//! Routes (all `GET`, all accepting an optional `date` query parameter in any format `--date`
+//! accepts):
+//!
+//! * `/{ubuntu,debian}/SELECTOR`: the releases matching a selector flag, e.g. `/ubuntu/supported`
+//! * `/{ubuntu,debian}/alias/SERIES`: the alias of a release, e.g. `/debian/alias/bookworm`
+//! * `/releases/SERIES`: the release with the given series, from either distro
+//!
+//! Errors are answered with `{"error": MESSAGE}`: 400 for an invalid date, 404 for an unknown
+//! route or series, and 503 when the data can't answer the query (e.g. it is outdated).
+//!
+//! Each distro's CSV file is reloaded when it changes (see `distro_info::handle`).
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::path::Path;
+
+use anyhow::{format_err, Error};
+use chrono::NaiveDate;
+use distro_info::clock::{self, Tz};
+use distro_info::date_expr::DateExpr;
+use distro_info::handle::DistroInfoHandle;
+use distro_info::{DebianDistroInfo, DistroInfo, DistroRelease, Selector, UbuntuDistroInfo};
+use serde_json::{json, Value};
+use tiny_http::{Header, Request, Response};
+
+use crate::alias_for;
+
+const UBUNTU_SELECTORS: [&str; 8] = [
+    "all",
+    "supported",
+    "supported-esm",
+    "unsupported",
+    "devel",
+    "stable",
+    "latest",
+    "lts",
+];
+const DEBIAN_SELECTORS: [&str; 9] = [
+    "all",
+    "supported",
+    "unsupported",
+    "devel",
+    "stable",
+    "testing",
+    "oldstable",
+    "lts",
+    "elts",
+];
+
+struct HttpError {
+    status: u16,
+    message: String,
+}
+
+impl HttpError {
+    fn not_found(message: String) -> Self {
+        Self {
+            status: 404,
+            message,
+        }
+    }
+
+    /// The data can't answer the query, e.g. there is no development release at the date
+    fn unavailable(message: String) -> Self {
+        Self {
+            status: 503,
+            message,
+        }
+    }
+}
+
+impl From<Error> for HttpError {
+    fn from(error: Error) -> Self {
+        Self {
+            status: 500,
+            message: error.to_string(),
+        }
+    }
+}
+
+fn release_json(distro_name: &str, distro_release: &DistroRelease) -> Value {
+    let mut value = serde_json::to_value(distro_release).unwrap();
+    value["distro"] = json!(distro_name.to_lowercase());
+    value
+}
+
+/// Decode `%XX` escapes; `+` is left as-is so that relative dates such as `+90d` work unescaped
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = vec![];
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let escaped = bytes
+            .get(idx + 1..idx + 3)
+            .filter(|_| bytes[idx] == b'%')
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                idx += 3;
+            }
+            None => {
+                decoded.push(bytes[idx]);
+                idx += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+pub struct Server {
+    http: tiny_http::Server,
+    ubuntu: DistroInfoHandle<UbuntuDistroInfo>,
+    debian: DistroInfoHandle<DebianDistroInfo>,
+    tz: Tz,
+}
+
+impl Server {
+    /// Load the given CSV files and listen on `addr`
+    pub fn bind(
+        addr: impl ToSocketAddrs,
+        ubuntu_csv: &Path,
+        debian_csv: &Path,
+        tz: Tz,
+    ) -> Result<Self, Error> {
+        let ubuntu = DistroInfoHandle::with_path(ubuntu_csv);
+        let debian = DistroInfoHandle::with_path(debian_csv);
+        // Load both files now, so that bad paths are reported at startup
+        ubuntu.get()?;
+        debian.get()?;
+        Ok(Self {
+            http: tiny_http::Server::http(addr).map_err(|e| format_err!("{}", e))?,
+            ubuntu,
+            debian,
+            tz,
+        })
+    }
+
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.http.server_addr().to_ip()
+    }
+
+    /// Serve requests until the listener fails
+    pub fn run(&self) -> Result<(), Error> {
+        loop {
+            self.handle_next()?;
+        }
+    }
+
+    /// Block until a request arrives, then answer it
+    ///
+    /// Only a failure to receive a request is an error; a failure to answer one (e.g. because the
+    /// client disconnected) is logged, so that one client can't stop the server.
+    pub fn handle_next(&self) -> Result<(), Error> {
+        let request = self.http.recv()?;
+        if let Err(error) = self.respond(request) {
+            eprintln!("distro-info-server: failed to respond: {}", error);
+        }
+        Ok(())
+    }
+
+    fn respond(&self, request: Request) -> Result<(), Error> {
+        let (status, body) = match self.route(request.url()) {
+            Ok(body) => (200, body),
+            Err(error) => (error.status, json!({ "error": error.message })),
+        };
+        let response = Response::from_string(body.to_string())
+            .with_status_code(status)
+            .with_header(Header::from_bytes("Content-Type", "application/json").unwrap());
+        Ok(request.respond(response)?)
+    }
+
+    fn route(&self, url: &str) -> Result<Value, HttpError> {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let date_expr = query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == "date")
+            .map(|(_, value)| percent_decode(value));
+        let segments: Vec<String> = path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(percent_decode)
+            .collect();
+        let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+        match segments.as_slice() {
+            ["ubuntu", rest @ ..] => {
+                let distro_info = self.ubuntu.get()?;
+                let date = self.resolve_date(&date_expr, &*distro_info)?;
+                self.query(&*distro_info, &UBUNTU_SELECTORS, rest, date)
+            }
+            ["debian", rest @ ..] => {
+                let distro_info = self.debian.get()?;
+                let date = self.resolve_date(&date_expr, &*distro_info)?;
+                self.query(&*distro_info, &DEBIAN_SELECTORS, rest, date)
+            }
+            ["releases", series] => {
+                let ubuntu = self.ubuntu.get()?;
+                let debian = self.debian.get()?;
+                ubuntu
+                    .iter()
+                    .find(|distro_release| distro_release.series() == series)
+                    .map(|distro_release| release_json("ubuntu", distro_release))
+                    .or_else(|| {
+                        debian
+                            .iter()
+                            .find(|distro_release| distro_release.series() == series)
+                            .map(|distro_release| release_json("debian", distro_release))
+                    })
+                    .ok_or_else(|| {
+                        HttpError::not_found(format!("unknown distribution series `{}'", series))
+                    })
+            }
+            _ => Err(HttpError::not_found(format!("no such route: {}", path))),
+        }
+    }
+
+    fn resolve_date(
+        &self,
+        date_expr: &Option<String>,
+        distro_info: &impl DistroInfo,
+    ) -> Result<NaiveDate, HttpError> {
+        let clock = clock::from_env(self.tz)?;
+        match date_expr {
+            Some(date_expr) => date_expr
+                .parse::<DateExpr>()
+                .and_then(|date_expr| date_expr.resolve(distro_info, &*clock))
+                .map_err(|error| HttpError {
+                    status: 400,
+                    message: error.to_string(),
+                }),
+            None => Ok(clock.today()),
+        }
+    }
+
+    fn query(
+        &self,
+        distro_info: &impl DistroInfo,
+        selectors: &[&str],
+        segments: &[&str],
+        date: NaiveDate,
+    ) -> Result<Value, HttpError> {
+        let distro_name = distro_info.distro().to_string();
+        match segments {
+            ["alias", series] => {
+                distro_info
+                    .select(&Selector::Series(series.to_string()), date)
+                    .map_err(|error| HttpError::not_found(error.to_string()))?;
+                Ok(json!({
+                    "series": series,
+                    "alias": alias_for(distro_info, series, date),
+                }))
+            }
+            [selector] if selectors.contains(selector) => Ok(distro_info
+                .select(&selector.parse::<Selector>()?, date)
+                .map_err(|error| HttpError::unavailable(error.to_string()))?
+                .into_iter()
+                .map(|distro_release| release_json(distro_name, distro_release))
+                .collect()),
+            _ => Err(HttpError::not_found(format!(
+                "no such {} route: /{}",
+                distro_name,
+                segments.join("/")
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    use distro_info::clock::Tz;
+    use serde_json::Value;
+
+    use super::{percent_decode, Server};
+
+    const UBUNTU_CSV: &str = "version,codename,series,created,release,eol,eol-server,eol-esm
+22.04 LTS,Jammy Jellyfish,jammy,2021-10-14,2022-04-21,2027-06-01,2027-06-01,2032-04-21
+24.04 LTS,Noble Numbat,noble,2023-10-12,2024-04-25,2029-05-31,2029-05-31,2034-04-25
+";
+    const DEBIAN_CSV: &str = "version,codename,series,created,release,eol,eol-lts,eol-elts
+12,Bookworm,bookworm,2021-08-14,2023-06-10,2026-07-11,2028-06-30,2033-06-30
+13,Trixie,trixie,2023-06-10,2025-08-09,2028-08-09,2030-06-30,2035-06-30
+14,Forky,forky,2025-08-09
+,Sid,sid,1993-08-16
+";
+
+    fn write_csv(dir: &Path, name: &str, content: &str) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn get(server: &Arc<Server>, path: &str) -> (u16, Value) {
+        let addr = server.local_addr().unwrap();
+        let handler = {
+            let server = server.clone();
+            thread::spawn(move || server.handle_next().unwrap())
+        };
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+            path
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        handler.join().unwrap();
+        let status = response[9..12].parse().unwrap();
+        let body = response.split_once("\r\n\r\n").unwrap().1;
+        (status, serde_json::from_str(body).unwrap())
+    }
+
+    fn series(body: &Value) -> Vec<&str> {
+        body.as_array()
+            .unwrap()
+            .iter()
+            .map(|release| release["series"].as_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn percent_decode_escapes() {
+        assert_eq!("+90d", percent_decode("+90d"));
+        assert_eq!("+90d", percent_decode("%2B90d"));
+        assert_eq!("100%", percent_decode("100%"));
+    }
+
+    #[test]
+    fn server_routes() {
+        let dir = std::env::temp_dir().join(format!("distro-info-server-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let ubuntu_csv = write_csv(&dir, "ubuntu.csv", UBUNTU_CSV);
+        let debian_csv = write_csv(&dir, "debian.csv", DEBIAN_CSV);
+        let server =
+            Arc::new(Server::bind("127.0.0.1:0", &ubuntu_csv, &debian_csv, Tz::Utc).unwrap());
+
+        let (status, body) = get(&server, "/ubuntu/supported?date=2024-01-01");
+        assert_eq!(200, status);
+        assert_eq!(vec!["jammy", "noble"], series(&body));
+        assert_eq!("ubuntu", body[0]["distro"]);
+        assert_eq!("2022-04-21", body[0]["release"]);
+
+        let (_, body) = get(&server, "/debian/stable?date=2024-01-01");
+        assert_eq!(vec!["bookworm"], series(&body));
+
+        let (_, body) = get(&server, "/debian/alias/bookworm?date=2025-09-01");
+        assert_eq!("oldstable", body["alias"]);
+
+        let (status, body) = get(&server, "/releases/trixie");
+        assert_eq!(200, status);
+        assert_eq!("debian", body["distro"]);
+        assert_eq!("Trixie", body["codename"]);
+
+        let (status, _) = get(&server, "/releases/warty");
+        assert_eq!(404, status);
+        let (status, _) = get(&server, "/ubuntu/testing");
+        assert_eq!(404, status);
+        let (status, _) = get(&server, "/ubuntu/all?date=soon");
+        assert_eq!(400, status);
+        let (status, body) = get(&server, "/ubuntu/lts?date=2020-01-01");
+        assert_eq!(503, status);
+        assert!(body["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("Distribution data outdated."));
+
+        // A client which disconnects without reading its response doesn't stop the server
+        let handler = {
+            let server = server.clone();
+            thread::spawn(move || server.handle_next())
+        };
+        let mut stream = TcpStream::connect(server.local_addr().unwrap()).unwrap();
+        write!(
+            stream,
+            "GET /ubuntu/all HTTP/1.1\r\nHost: localhost\r\n\r\n"
+        )
+        .unwrap();
+        drop(stream);
+        assert!(handler.join().unwrap().is_ok());
+        let (status, _) = get(&server, "/ubuntu/all");
+        assert_eq!(200, status);
+
+        // Changes to the CSV file are picked up
+        let mtime = fs::metadata(&ubuntu_csv).unwrap().modified().unwrap();
+        let oracular = "24.10,Oracular Oriole,oracular,2024-04-25,2024-10-10,2025-07-10\n";
+        write_csv(&dir, "ubuntu.csv", &format!("{}{}", UBUNTU_CSV, oracular));
+        fs::File::options()
+            .append(true)
+            .open(&ubuntu_csv)
+            .unwrap()
+            .set_modified(mtime + Duration::from_secs(1))
+            .unwrap();
+        let (_, body) = get(&server, "/ubuntu/all");
+        assert_eq!(vec!["jammy", "noble", "oracular"], series(&body));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,364 @@
+use anyhow::{bail, Error};
+use chrono::NaiveDate;
+use distro_info::DistroRelease;
+
+#[derive(Clone)]
+pub enum TableFormat {
+    Text,
+    Markdown,
+    Html,
+}
+
+impl TableFormat {
+    pub fn from_cli(value: &str) -> Result<Self, Error> {
+        match value {
+            "text" => Ok(Self::Text),
+            "markdown" => Ok(Self::Markdown),
+            "html" => Ok(Self::Html),
+            _ => bail!(
+                "unknown table format `{}'; must be one of text, markdown, html",
+                value
+            ),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub enum TableColumn {
+    Series,
+    Version,
+    Codename,
+    Created,
+    Release,
+    Eol,
+    EolLTS,
+    EolELTS,
+    EolESM,
+    EolServer,
+    Status,
+}
+
+const MILESTONE_COLUMNS: [TableColumn; 5] = [
+    TableColumn::Eol,
+    TableColumn::EolLTS,
+    TableColumn::EolELTS,
+    TableColumn::EolESM,
+    TableColumn::EolServer,
+];
+
+impl TableColumn {
+    pub fn from_cli(value: &str) -> Result<Self, Error> {
+        match value {
+            "series" => Ok(Self::Series),
+            "version" => Ok(Self::Version),
+            "codename" => Ok(Self::Codename),
+            "created" => Ok(Self::Created),
+            "release" => Ok(Self::Release),
+            "eol" => Ok(Self::Eol),
+            "eol-lts" => Ok(Self::EolLTS),
+            "eol-elts" => Ok(Self::EolELTS),
+            "eol-esm" => Ok(Self::EolESM),
+            "eol-server" => Ok(Self::EolServer),
+            "status" => Ok(Self::Status),
+            _ => bail!("unknown table column `{}'", value),
+        }
+    }
+
+    fn heading(&self) -> &'static str {
+        match self {
+            Self::Series => "series",
+            Self::Version => "version",
+            Self::Codename => "codename",
+            Self::Created => "created",
+            Self::Release => "release",
+            Self::Eol => "eol",
+            Self::EolLTS => "eol-lts",
+            Self::EolELTS => "eol-elts",
+            Self::EolESM => "eol-esm",
+            Self::EolServer => "eol-server",
+            Self::Status => "status",
+        }
+    }
+
+    fn date(&self, distro_release: &DistroRelease) -> Option<NaiveDate> {
+        *match self {
+            Self::Created => distro_release.created(),
+            Self::Release => distro_release.release(),
+            Self::Eol => distro_release.eol(),
+            Self::EolLTS => distro_release.eol_lts(),
+            Self::EolELTS => distro_release.eol_elts(),
+            Self::EolESM => distro_release.eol_esm(),
+            Self::EolServer => distro_release.eol_server(),
+            Self::Series | Self::Version | Self::Codename | Self::Status => &None,
+        }
+    }
+
+    fn cell(&self, distro_release: &DistroRelease, date: NaiveDate) -> String {
+        match self {
+            Self::Series => distro_release.series().to_string(),
+            Self::Version => distro_release.version().clone().unwrap_or_default(),
+            Self::Codename => distro_release.codename().to_string(),
+            Self::Status => status(distro_release, date).to_string(),
+            _ => self
+                .date(distro_release)
+                .map(|date| date.to_string())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// The support status of `distro_release` at `date`, for display
+pub fn status(distro_release: &DistroRelease, date: NaiveDate) -> &'static str {
+    match distro_release.phase_at(date) {
+        Some(phase) => phase.name(),
+        None if distro_release.released_at(date) => "EOL",
+        None => "unreleased",
+    }
+}
+
+/// The columns shown when none are requested: identifying details, the release date, every EOL
+/// milestone that any of `distro_releases` has, and the status
+pub fn default_columns(distro_releases: &[&DistroRelease]) -> Vec<TableColumn> {
+    let mut columns = vec![
+        TableColumn::Series,
+        TableColumn::Version,
+        TableColumn::Codename,
+        TableColumn::Release,
+    ];
+    columns.extend(MILESTONE_COLUMNS.into_iter().filter(|column| {
+        distro_releases
+            .iter()
+            .any(|distro_release| column.date(distro_release).is_some())
+    }));
+    columns.push(TableColumn::Status);
+    columns
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+pub fn render(
+    format: &TableFormat,
+    columns: &[TableColumn],
+    distro_releases: &[&DistroRelease],
+    date: NaiveDate,
+) -> String {
+    let headings: Vec<String> = columns
+        .iter()
+        .map(|column| column.heading().to_string())
+        .collect();
+    let rows: Vec<Vec<String>> = distro_releases
+        .iter()
+        .map(|distro_release| {
+            columns
+                .iter()
+                .map(|column| column.cell(distro_release, date))
+                .collect()
+        })
+        .collect();
+    let mut lines = vec![];
+    match format {
+        TableFormat::Text => {
+            let widths: Vec<usize> = (0..columns.len())
+                .map(|idx| {
+                    rows.iter()
+                        .chain([&headings])
+                        .map(|row| row[idx].chars().count())
+                        .max()
+                        .unwrap_or(0)
+                })
+                .collect();
+            let format_row = |row: &[String]| -> String {
+                row.iter()
+                    .zip(&widths)
+                    .map(|(cell, width)| format!("{:width$}", cell, width = width))
+                    .collect::<Vec<_>>()
+                    .join("  ")
+                    .trim_end()
+                    .to_string()
+            };
+            let rule: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+            lines.push(format_row(&headings));
+            lines.push(format_row(&rule));
+            lines.extend(rows.iter().map(|row| format_row(row)));
+        }
+        TableFormat::Markdown => {
+            let format_row = |row: &[String]| -> String {
+                let cells: Vec<String> = row.iter().map(|cell| cell.replace('|', "\\|")).collect();
+                format!("| {} |", cells.join(" | "))
+            };
+            lines.push(format_row(&headings));
+            lines.push(format!("|{}", " --- |".repeat(columns.len())));
+            lines.extend(rows.iter().map(|row| format_row(row)));
+        }
+        TableFormat::Html => {
+            let format_row = |row: &[String], tag: &str| -> String {
+                let cells: Vec<String> = row
+                    .iter()
+                    .map(|cell| format!("<{tag}>{}</{tag}>", escape_html(cell)))
+                    .collect();
+                format!("    <tr>{}</tr>", cells.join(""))
+            };
+            lines.push("<table>".to_string());
+            lines.push("  <thead>".to_string());
+            lines.push(format_row(&headings, "th"));
+            lines.push("  </thead>".to_string());
+            lines.push("  <tbody>".to_string());
+            lines.extend(rows.iter().map(|row| format_row(row, "td")));
+            lines.push("  </tbody>".to_string());
+            lines.push("</table>".to_string());
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use distro_info::{DistroInfo, UbuntuDistroInfo};
+
+    use super::{default_columns, render, status, TableColumn, TableFormat};
+
+    const CSV: &str = "version,codename,series,created,release,eol,eol-esm
+18.04 LTS,Bionic Beaver,bionic,2017-10-19,2018-04-26,2023-05-31,2028-04-26
+20.10,Groovy <G> & |H|,groovy,2020-04-23,2020-10-22,2021-07-22
+22.04 LTS,Jammy Jellyfish,jammy,2021-10-14,2022-04-21,2027-06-01,2032-04-21
+24.10,Oracular Oriole,oracular,2024-04-25,2024-10-10,2025-07-10
+";
+
+    fn distro_info() -> UbuntuDistroInfo {
+        UbuntuDistroInfo::from_csv_reader(
+            csv::ReaderBuilder::new()
+                .flexible(true)
+                .from_reader(CSV.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 6, 1).unwrap()
+    }
+
+    #[test]
+    fn statuses() {
+        let distro_info = distro_info();
+        let statuses: Vec<&str> = distro_info
+            .iter()
+            .map(|distro_release| status(distro_release, date()))
+            .collect();
+        assert_eq!(vec!["ESM", "EOL", "standard", "unreleased"], statuses);
+    }
+
+    #[test]
+    fn columns() {
+        let distro_info = distro_info();
+        let distro_releases: Vec<_> = distro_info.iter().collect();
+        let headings = |distro_releases| -> Vec<&str> {
+            default_columns(distro_releases)
+                .iter()
+                .map(TableColumn::heading)
+                .collect()
+        };
+        assert_eq!(
+            vec!["series", "version", "codename", "release", "eol", "eol-esm", "status"],
+            headings(&distro_releases)
+        );
+        // Only milestones which some release has are included
+        assert_eq!(
+            vec!["series", "version", "codename", "release", "eol", "status"],
+            headings(&distro_releases[1..2])
+        );
+        assert!(TableColumn::from_cli("eol-legacy").is_err());
+        assert!(TableFormat::from_cli("csv").is_err());
+    }
+
+    #[test]
+    fn render_text() {
+        let distro_info = distro_info();
+        let distro_releases: Vec<_> = distro_info.iter().collect();
+        assert_eq!(
+            "\
+series    version    codename          release     eol         eol-esm     status
+--------  ---------  ----------------  ----------  ----------  ----------  ----------
+bionic    18.04 LTS  Bionic Beaver     2018-04-26  2023-05-31  2028-04-26  ESM
+groovy    20.10      Groovy <G> & |H|  2020-10-22  2021-07-22              EOL
+jammy     22.04 LTS  Jammy Jellyfish   2022-04-21  2027-06-01  2032-04-21  standard
+oracular  24.10      Oracular Oriole   2024-10-10  2025-07-10              unreleased",
+            render(
+                &TableFormat::Text,
+                &default_columns(&distro_releases),
+                &distro_releases,
+                date()
+            )
+        );
+        assert_eq!(
+            "\
+series  created
+------  ----------
+bionic  2017-10-19
+groovy  2020-04-23",
+            render(
+                &TableFormat::Text,
+                &[TableColumn::Series, TableColumn::Created],
+                &distro_releases[..2],
+                date()
+            )
+        );
+    }
+
+    #[test]
+    fn render_markdown() {
+        let distro_info = distro_info();
+        let distro_releases: Vec<_> = distro_info.iter().collect();
+        assert_eq!(
+            "\
+| series | codename | eol-esm | status |
+| --- | --- | --- | --- |
+| bionic | Bionic Beaver | 2028-04-26 | ESM |
+| groovy | Groovy <G> & \\|H\\| |  | EOL |",
+            render(
+                &TableFormat::Markdown,
+                &[
+                    TableColumn::Series,
+                    TableColumn::Codename,
+                    TableColumn::EolESM,
+                    TableColumn::Status
+                ],
+                &distro_releases[..2],
+                date()
+            )
+        );
+    }
+
+    #[test]
+    fn render_html() {
+        let distro_info = distro_info();
+        let distro_releases: Vec<_> = distro_info.iter().collect();
+        assert_eq!(
+            "\
+<table>
+  <thead>
+    <tr><th>series</th><th>codename</th><th>eol-esm</th></tr>
+  </thead>
+  <tbody>
+    <tr><td>bionic</td><td>Bionic Beaver</td><td>2028-04-26</td></tr>
+    <tr><td>groovy</td><td>Groovy &lt;G&gt; &amp; |H|</td><td></td></tr>
+  </tbody>
+</table>",
+            render(
+                &TableFormat::Html,
+                &[
+                    TableColumn::Series,
+                    TableColumn::Codename,
+                    TableColumn::EolESM
+                ],
+                &distro_releases[..2],
+                date()
+            )
+        );
+    }
+}
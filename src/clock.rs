@@ -0,0 +1,211 @@
+//! Determine "today", for defaulting dates.
+//!
+//! The current date depends on both the current time and the timezone it is observed in: an EOL
+//! date flips at local midnight for someone in UTC+10, ten hours before it does in UTC.  Callers
+//! that need reproducible results (tests, or builds honouring `SOURCE_DATE_EPOCH`) can supply a
+//! fixed clock instead of the system clock.
+use std::env;
+use std::str::FromStr;
+
+use anyhow::Error;
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, Utc};
+
+/// The environment variable used by reproducible builds to fix the current time
+pub const SOURCE_DATE_EPOCH: &str = "SOURCE_DATE_EPOCH";
+
+/// A source of the current date
+pub trait Clock {
+    fn today(&self) -> NaiveDate;
+}
+
+/// The timezone in which the current date is determined
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tz {
+    Utc,
+    Local,
+    Fixed(FixedOffset),
+}
+
+impl FromStr for Tz {
+    type Err = Error;
+
+    /// Parse `UTC`, `local` or a fixed offset such as `+05:30` or `-0800`
+    fn from_str(value: &str) -> Result<Self, Error> {
+        match value {
+            "UTC" | "utc" | "Z" => Ok(Tz::Utc),
+            "local" => Ok(Tz::Local),
+            _ => {
+                let digits = value.replace(':', "");
+                let sign = match digits.get(..1) {
+                    Some("+") => 1,
+                    Some("-") => -1,
+                    _ => bail!(
+                        "invalid timezone `{}'; must be UTC, local or an offset such as +05:30",
+                        value
+                    ),
+                };
+                let (hours, minutes) = match digits.len() {
+                    // Slicing by byte index below needs every character to be a single byte
+                    _ if !digits.is_ascii() => (None, None),
+                    3 => (digits[1..3].parse::<i32>().ok(), Some(0)),
+                    5 => (
+                        digits[1..3].parse::<i32>().ok(),
+                        digits[3..5].parse::<i32>().ok(),
+                    ),
+                    _ => (None, None),
+                };
+                hours
+                    .zip(minutes)
+                    .and_then(|(hours, minutes)| {
+                        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+                    })
+                    .map(Tz::Fixed)
+                    .ok_or_else(|| format_err!("invalid timezone offset `{}'", value))
+            }
+        }
+    }
+}
+
+impl Tz {
+    /// The date on which `instant` falls in this timezone
+    pub fn date_of(&self, instant: DateTime<Utc>) -> NaiveDate {
+        match self {
+            Tz::Utc => instant.date_naive(),
+            Tz::Local => instant.with_timezone(&Local).date_naive(),
+            Tz::Fixed(offset) => instant.with_timezone(offset).date_naive(),
+        }
+    }
+}
+
+/// A clock reading the system time, observed in the given timezone
+pub struct SystemClock {
+    tz: Tz,
+}
+
+impl SystemClock {
+    pub fn new(tz: Tz) -> Self {
+        Self { tz }
+    }
+}
+
+impl Clock for SystemClock {
+    fn today(&self) -> NaiveDate {
+        self.tz.date_of(Utc::now())
+    }
+}
+
+/// A clock which is always on the same date
+pub struct FixedClock {
+    date: NaiveDate,
+}
+
+impl FixedClock {
+    pub fn new(date: NaiveDate) -> Self {
+        Self { date }
+    }
+
+    /// A clock fixed at `timestamp` (in seconds since the Unix epoch), observed in `tz`
+    pub fn from_timestamp(timestamp: i64, tz: Tz) -> Result<Self, Error> {
+        let instant = DateTime::from_timestamp(timestamp, 0)
+            .ok_or_else(|| format_err!("timestamp {} is out of range", timestamp))?;
+        Ok(Self::new(tz.date_of(instant)))
+    }
+}
+
+impl Clock for FixedClock {
+    fn today(&self) -> NaiveDate {
+        self.date
+    }
+}
+
+/// Returns a clock fixed at `source_date_epoch` if it is given, otherwise the system clock
+pub fn from_source_date_epoch(
+    source_date_epoch: Option<&str>,
+    tz: Tz,
+) -> Result<Box<dyn Clock>, Error> {
+    Ok(match source_date_epoch {
+        Some(value) => {
+            let timestamp = value.parse::<i64>().map_err(|_| {
+                format_err!(
+                    "invalid {} `{}'; must be an integer",
+                    SOURCE_DATE_EPOCH,
+                    value
+                )
+            })?;
+            Box::new(FixedClock::from_timestamp(timestamp, tz)?)
+        }
+        None => Box::new(SystemClock::new(tz)),
+    })
+}
+
+/// Returns a clock honouring `SOURCE_DATE_EPOCH` if it is set, otherwise the system clock
+pub fn from_env(tz: Tz) -> Result<Box<dyn Clock>, Error> {
+    from_source_date_epoch(env::var(SOURCE_DATE_EPOCH).ok().as_deref(), tz)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, FixedOffset};
+
+    use super::{from_source_date_epoch, Clock, FixedClock, Tz};
+    use crate::tests::naive_date;
+
+    #[test]
+    fn tz_from_str() {
+        assert_eq!(Tz::Utc, "UTC".parse().unwrap());
+        assert_eq!(Tz::Local, "local".parse().unwrap());
+        assert_eq!(
+            Tz::Fixed(FixedOffset::east_opt(5 * 3600 + 30 * 60).unwrap()),
+            "+05:30".parse().unwrap()
+        );
+        assert_eq!(
+            Tz::Fixed(FixedOffset::west_opt(8 * 3600).unwrap()),
+            "-0800".parse().unwrap()
+        );
+        assert_eq!(
+            Tz::Fixed(FixedOffset::east_opt(10 * 3600).unwrap()),
+            "+10".parse().unwrap()
+        );
+        for value in [
+            "",
+            "Europe/London",
+            "+5:30",
+            "+99:00",
+            "05:30",
+            "+1é1",
+            "+é",
+        ] {
+            assert!(value.parse::<Tz>().is_err(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn tz_date_of() {
+        // 2024-04-25T20:00:00Z
+        let instant = DateTime::from_timestamp(1714075200, 0).unwrap();
+        assert_eq!(naive_date(2024, 4, 25), Tz::Utc.date_of(instant));
+        assert_eq!(
+            naive_date(2024, 4, 26),
+            "+10:00".parse::<Tz>().unwrap().date_of(instant)
+        );
+        assert_eq!(
+            naive_date(2024, 4, 25),
+            "-05:00".parse::<Tz>().unwrap().date_of(instant)
+        );
+    }
+
+    #[test]
+    fn fixed_clock() {
+        let clock = FixedClock::new(naive_date(2024, 4, 25));
+        assert_eq!(naive_date(2024, 4, 25), clock.today());
+    }
+
+    #[test]
+    fn source_date_epoch_clock() {
+        let clock = from_source_date_epoch(Some("1714075200"), Tz::Utc).unwrap();
+        assert_eq!(naive_date(2024, 4, 25), clock.today());
+        let clock = from_source_date_epoch(Some("1714075200"), "+10:00".parse().unwrap()).unwrap();
+        assert_eq!(naive_date(2024, 4, 26), clock.today());
+        assert!(from_source_date_epoch(Some("yesterday"), Tz::Utc).is_err());
+    }
+}
@@ -0,0 +1,669 @@
+//! An API mirroring the `distro_info` Python module, for porting code which uses it.
+//!
+//! `UbuntuDistroInfo` and `DebianDistroInfo` here wrap the types of the same name in the crate
+//! root, and their methods follow the Python ones: dates default to today, `result` selects
+//! whether releases are returned as their series (`codename`), full names (`fullname`), versions
+//! (`release`) or the releases themselves (`object`), and methods which select a single release
+//! fail with `OUTDATED_MSG` if there isn't one.  (As in Python, "codename" means the series,
+//! e.g. `jammy`, not the codename, e.g. `Jammy Jellyfish`.)
+//!
+//! Today is read from `clock::from_env` in UTC; each method defaulting to it has a `_with_clock`
+//! variant taking the `Clock` to read it from instead.
+use std::str::FromStr;
+
+use anyhow::Error;
+use chrono::NaiveDate;
+
+use crate::clock::{self, Clock, Tz};
+use crate::{DistroInfo, DistroRelease, OUTDATED_MSG};
+
+/// How a release should be returned, per the `result` argument of the Python methods
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ResultFormat {
+    /// The series, e.g. `jammy`
+    Codename,
+    /// e.g. `Ubuntu 22.04 LTS "Jammy Jellyfish"`
+    Fullname,
+    /// The version, e.g. `22.04 LTS`
+    Release,
+    /// The `DistroRelease` itself
+    Object,
+}
+
+impl FromStr for ResultFormat {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Error> {
+        match value {
+            "codename" => Ok(ResultFormat::Codename),
+            "fullname" => Ok(ResultFormat::Fullname),
+            "release" => Ok(ResultFormat::Release),
+            "object" => Ok(ResultFormat::Object),
+            _ => bail!("Only codename, fullname, release, and object are allowed result values"),
+        }
+    }
+}
+
+/// A release, as returned in the requested `ResultFormat`
+#[derive(Clone, Debug, PartialEq)]
+pub enum Formatted<'a> {
+    Text(String),
+    Object(&'a DistroRelease),
+}
+
+impl Formatted<'_> {
+    /// The text of a non-`Object` result
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Formatted::Text(text) => Some(text),
+            Formatted::Object(_) => None,
+        }
+    }
+}
+
+/// The clock which dates default to when no clock is given: the system clock in UTC, unless
+/// `SOURCE_DATE_EPOCH` is set
+fn default_clock() -> Result<Box<dyn Clock>, Error> {
+    clock::from_env(Tz::Utc)
+}
+
+/// The methods of the Python `DistroInfo` base class
+pub trait CompatDistroInfo {
+    /// The wrapped release data
+    fn distro_info(&self) -> &impl DistroInfo;
+
+    /// Returns `distro_release` as requested by `result`
+    fn format<'a>(&self, result: ResultFormat, distro_release: &'a DistroRelease) -> Formatted<'a> {
+        let version = distro_release.version().clone().unwrap_or_default();
+        match result {
+            ResultFormat::Codename => Formatted::Text(distro_release.series().clone()),
+            ResultFormat::Fullname => Formatted::Text(format!(
+                "{} {} \"{}\"",
+                self.distro_info().distro().to_string(),
+                version,
+                distro_release.codename()
+            )),
+            ResultFormat::Release => Formatted::Text(version),
+            ResultFormat::Object => Formatted::Object(distro_release),
+        }
+    }
+
+    /// Releases which had been created at `date`
+    fn avail(&self, date: NaiveDate) -> Vec<&DistroRelease> {
+        self.distro_info().all_at(date)
+    }
+
+    /// The series of every release (`all`)
+    fn all(&self) -> Vec<&str> {
+        self.distro_info()
+            .iter()
+            .map(|distro_release| distro_release.series().as_str())
+            .collect()
+    }
+
+    /// Every release, as requested by `result`
+    fn get_all(&self, result: ResultFormat) -> Vec<Formatted<'_>> {
+        self.distro_info()
+            .iter()
+            .map(|distro_release| self.format(result, distro_release))
+            .collect()
+    }
+
+    /// Map codename aliases to the series they describe; there are none by default
+    fn codename(
+        &self,
+        release: &str,
+        date: Option<NaiveDate>,
+        default: Option<&str>,
+    ) -> Result<Option<String>, Error> {
+        self.codename_with_clock(release, date, default, &*default_clock()?)
+    }
+
+    /// As `codename`, with `date` defaulting to today according to `clock`
+    fn codename_with_clock(
+        &self,
+        release: &str,
+        _date: Option<NaiveDate>,
+        _default: Option<&str>,
+        _clock: &dyn Clock,
+    ) -> Result<Option<String>, Error> {
+        Ok(Some(release.to_string()))
+    }
+
+    /// The version of the release with the given codename or series, or `default`
+    fn version(&self, name: &str, default: Option<&str>) -> Option<String> {
+        self.distro_info()
+            .iter()
+            .find(|distro_release| {
+                distro_release.codename() == name || distro_release.series() == name
+            })
+            .map(|distro_release| distro_release.version().clone().unwrap_or_default())
+            .or_else(|| default.map(str::to_string))
+    }
+
+    /// Whether `codename` is the series of a known release
+    fn valid(&self, codename: &str) -> bool {
+        self.all().contains(&codename)
+    }
+
+    /// The latest release in development at `date`
+    fn devel(&self, date: Option<NaiveDate>, result: ResultFormat) -> Result<Formatted<'_>, Error> {
+        self.devel_with_clock(date, result, &*default_clock()?)
+    }
+
+    /// As `devel`, with `date` defaulting to today according to `clock`
+    fn devel_with_clock(
+        &self,
+        date: Option<NaiveDate>,
+        result: ResultFormat,
+        clock: &dyn Clock,
+    ) -> Result<Formatted<'_>, Error> {
+        let date = date.unwrap_or_else(|| clock.today());
+        let distros = self.devel_candidates(date);
+        match distros.last() {
+            Some(distro_release) => Ok(self.format(result, distro_release)),
+            None => bail!(OUTDATED_MSG),
+        }
+    }
+
+    /// Releases which were unreleased, or released after `date` and not yet EOL
+    fn devel_candidates(&self, date: NaiveDate) -> Vec<&DistroRelease> {
+        self.avail(date)
+            .into_iter()
+            .filter(|distro_release| match distro_release.release() {
+                None => true,
+                Some(release) => {
+                    date < *release && distro_release.eol().is_none_or(|eol| date <= eol)
+                }
+            })
+            .collect()
+    }
+
+    /// The latest release which was released and not yet EOL at `date`
+    fn stable(
+        &self,
+        date: Option<NaiveDate>,
+        result: ResultFormat,
+    ) -> Result<Formatted<'_>, Error> {
+        self.stable_with_clock(date, result, &*default_clock()?)
+    }
+
+    /// As `stable`, with `date` defaulting to today according to `clock`
+    fn stable_with_clock(
+        &self,
+        date: Option<NaiveDate>,
+        result: ResultFormat,
+        clock: &dyn Clock,
+    ) -> Result<Formatted<'_>, Error> {
+        let date = date.unwrap_or_else(|| clock.today());
+        self.avail(date)
+            .into_iter()
+            .rfind(|distro_release| {
+                distro_release.released_at(date)
+                    && distro_release.eol().is_none_or(|eol| date <= eol)
+            })
+            .map(|distro_release| self.format(result, distro_release))
+            .ok_or_else(|| format_err!(OUTDATED_MSG))
+    }
+
+    /// Whether `distro_release` was supported at `date`
+    fn is_supported(&self, distro_release: &DistroRelease, date: NaiveDate) -> bool {
+        distro_release.eol().is_none_or(|eol| date <= eol)
+    }
+
+    /// Releases which had been created and not yet reached EOL at `date`
+    fn supported(
+        &self,
+        date: Option<NaiveDate>,
+        result: ResultFormat,
+    ) -> Result<Vec<Formatted<'_>>, Error> {
+        self.supported_with_clock(date, result, &*default_clock()?)
+    }
+
+    /// As `supported`, with `date` defaulting to today according to `clock`
+    fn supported_with_clock(
+        &self,
+        date: Option<NaiveDate>,
+        result: ResultFormat,
+        clock: &dyn Clock,
+    ) -> Result<Vec<Formatted<'_>>, Error> {
+        let date = date.unwrap_or_else(|| clock.today());
+        Ok(self
+            .avail(date)
+            .into_iter()
+            .filter(|distro_release| self.is_supported(distro_release, date))
+            .map(|distro_release| self.format(result, distro_release))
+            .collect())
+    }
+
+    /// Releases which had been created but were no longer supported at `date`
+    fn unsupported(
+        &self,
+        date: Option<NaiveDate>,
+        result: ResultFormat,
+    ) -> Result<Vec<Formatted<'_>>, Error> {
+        self.unsupported_with_clock(date, result, &*default_clock()?)
+    }
+
+    /// As `unsupported`, with `date` defaulting to today according to `clock`
+    fn unsupported_with_clock(
+        &self,
+        date: Option<NaiveDate>,
+        result: ResultFormat,
+        clock: &dyn Clock,
+    ) -> Result<Vec<Formatted<'_>>, Error> {
+        let date = date.unwrap_or_else(|| clock.today());
+        Ok(self
+            .avail(date)
+            .into_iter()
+            .filter(|distro_release| !self.is_supported(distro_release, date))
+            .map(|distro_release| self.format(result, distro_release))
+            .collect())
+    }
+}
+
+/// The Python `UbuntuDistroInfo`
+pub struct UbuntuDistroInfo(crate::UbuntuDistroInfo);
+
+impl UbuntuDistroInfo {
+    pub fn new() -> Result<Self, Error> {
+        Ok(Self(crate::UbuntuDistroInfo::new()?))
+    }
+
+    /// The latest LTS release supported at `date`
+    pub fn lts(
+        &self,
+        date: Option<NaiveDate>,
+        result: ResultFormat,
+    ) -> Result<Formatted<'_>, Error> {
+        self.lts_with_clock(date, result, &*default_clock()?)
+    }
+
+    /// As `lts`, with `date` defaulting to today according to `clock`
+    pub fn lts_with_clock(
+        &self,
+        date: Option<NaiveDate>,
+        result: ResultFormat,
+        clock: &dyn Clock,
+    ) -> Result<Formatted<'_>, Error> {
+        let date = date.unwrap_or_else(|| clock.today());
+        self.avail(date)
+            .into_iter()
+            .rfind(|distro_release| {
+                distro_release.ubuntu_is_lts()
+                    && distro_release.released_at(date)
+                    && distro_release.eol().is_some_and(|eol| date <= eol)
+            })
+            .map(|distro_release| self.format(result, distro_release))
+            .ok_or_else(|| format_err!(OUTDATED_MSG))
+    }
+
+    /// Whether the release with series `codename` is an LTS release
+    pub fn is_lts(&self, codename: &str) -> bool {
+        self.0
+            .iter()
+            .find(|distro_release| distro_release.series() == codename)
+            .is_some_and(|distro_release| distro_release.ubuntu_is_lts())
+    }
+
+    /// Releases which had been created and were still covered by ESM at `date`
+    pub fn supported_esm(
+        &self,
+        date: Option<NaiveDate>,
+        result: ResultFormat,
+    ) -> Result<Vec<Formatted<'_>>, Error> {
+        self.supported_esm_with_clock(date, result, &*default_clock()?)
+    }
+
+    /// As `supported_esm`, with `date` defaulting to today according to `clock`
+    pub fn supported_esm_with_clock(
+        &self,
+        date: Option<NaiveDate>,
+        result: ResultFormat,
+        clock: &dyn Clock,
+    ) -> Result<Vec<Formatted<'_>>, Error> {
+        let date = date.unwrap_or_else(|| clock.today());
+        Ok(self
+            .avail(date)
+            .into_iter()
+            .filter(|distro_release| distro_release.eol_esm().is_some_and(|eol| date <= eol))
+            .map(|distro_release| self.format(result, distro_release))
+            .collect())
+    }
+}
+
+impl From<crate::UbuntuDistroInfo> for UbuntuDistroInfo {
+    fn from(distro_info: crate::UbuntuDistroInfo) -> Self {
+        Self(distro_info)
+    }
+}
+
+impl CompatDistroInfo for UbuntuDistroInfo {
+    fn distro_info(&self) -> &impl DistroInfo {
+        &self.0
+    }
+
+    /// Ubuntu releases are also supported until their server EOL date
+    fn is_supported(&self, distro_release: &DistroRelease, date: NaiveDate) -> bool {
+        distro_release.eol().is_none_or(|eol| date <= eol)
+            || distro_release
+                .eol_server()
+                .is_some_and(|eol_server| date <= eol_server)
+    }
+}
+
+/// The Python `DebianDistroInfo`
+pub struct DebianDistroInfo(crate::DebianDistroInfo);
+
+/// The aliases Debian uses for its releases
+const ALIASES: [&str; 4] = ["unstable", "testing", "stable", "oldstable"];
+
+impl DebianDistroInfo {
+    pub fn new() -> Result<Self, Error> {
+        Ok(Self(crate::DebianDistroInfo::new()?))
+    }
+
+    /// The last stable release before the current one at `date`
+    pub fn old(
+        &self,
+        date: Option<NaiveDate>,
+        result: ResultFormat,
+    ) -> Result<Formatted<'_>, Error> {
+        self.old_with_clock(date, result, &*default_clock()?)
+    }
+
+    /// As `old`, with `date` defaulting to today according to `clock`
+    pub fn old_with_clock(
+        &self,
+        date: Option<NaiveDate>,
+        result: ResultFormat,
+        clock: &dyn Clock,
+    ) -> Result<Formatted<'_>, Error> {
+        let date = date.unwrap_or_else(|| clock.today());
+        let distros: Vec<_> = self
+            .avail(date)
+            .into_iter()
+            .filter(|distro_release| distro_release.released_at(date))
+            .collect();
+        match distros.len().checked_sub(2) {
+            Some(index) => Ok(self.format(result, distros[index])),
+            None => bail!(OUTDATED_MSG),
+        }
+    }
+
+    /// The release which was testing at `date`
+    pub fn testing(
+        &self,
+        date: Option<NaiveDate>,
+        result: ResultFormat,
+    ) -> Result<Formatted<'_>, Error> {
+        self.testing_with_clock(date, result, &*default_clock()?)
+    }
+
+    /// As `testing`, with `date` defaulting to today according to `clock`
+    pub fn testing_with_clock(
+        &self,
+        date: Option<NaiveDate>,
+        result: ResultFormat,
+        clock: &dyn Clock,
+    ) -> Result<Formatted<'_>, Error> {
+        let date = date.unwrap_or_else(|| clock.today());
+        self.distro_info()
+            .debian_testing(date)
+            .map(|distro_release| self.format(result, distro_release))
+            .ok_or_else(|| format_err!(OUTDATED_MSG))
+    }
+
+    /// Releases which were past EOL but covered by LTS at `date`
+    pub fn lts_supported(
+        &self,
+        date: Option<NaiveDate>,
+        result: ResultFormat,
+    ) -> Result<Vec<Formatted<'_>>, Error> {
+        self.lts_supported_with_clock(date, result, &*default_clock()?)
+    }
+
+    /// As `lts_supported`, with `date` defaulting to today according to `clock`
+    pub fn lts_supported_with_clock(
+        &self,
+        date: Option<NaiveDate>,
+        result: ResultFormat,
+        clock: &dyn Clock,
+    ) -> Result<Vec<Formatted<'_>>, Error> {
+        let date = date.unwrap_or_else(|| clock.today());
+        Ok(self
+            .avail(date)
+            .into_iter()
+            .filter(|distro_release| {
+                distro_release.eol().is_some_and(|eol| date > eol)
+                    && distro_release
+                        .eol_lts()
+                        .is_some_and(|eol_lts| date <= eol_lts)
+            })
+            .map(|distro_release| self.format(result, distro_release))
+            .collect())
+    }
+
+    /// Releases which were past LTS but covered by ELTS at `date`
+    pub fn elts_supported(
+        &self,
+        date: Option<NaiveDate>,
+        result: ResultFormat,
+    ) -> Result<Vec<Formatted<'_>>, Error> {
+        self.elts_supported_with_clock(date, result, &*default_clock()?)
+    }
+
+    /// As `elts_supported`, with `date` defaulting to today according to `clock`
+    pub fn elts_supported_with_clock(
+        &self,
+        date: Option<NaiveDate>,
+        result: ResultFormat,
+        clock: &dyn Clock,
+    ) -> Result<Vec<Formatted<'_>>, Error> {
+        let date = date.unwrap_or_else(|| clock.today());
+        Ok(self
+            .avail(date)
+            .into_iter()
+            .filter(|distro_release| {
+                distro_release
+                    .eol_lts()
+                    .is_some_and(|eol_lts| date > eol_lts)
+                    && distro_release
+                        .eol_elts()
+                        .is_some_and(|eol_elts| date <= eol_elts)
+            })
+            .map(|distro_release| self.format(result, distro_release))
+            .collect())
+    }
+}
+
+impl From<crate::DebianDistroInfo> for DebianDistroInfo {
+    fn from(distro_info: crate::DebianDistroInfo) -> Self {
+        Self(distro_info)
+    }
+}
+
+impl CompatDistroInfo for DebianDistroInfo {
+    fn distro_info(&self) -> &impl DistroInfo {
+        &self.0
+    }
+
+    /// Map `unstable`, `testing`, `stable` and `oldstable` to the series they described at `date`;
+    /// anything else maps to `default`
+    fn codename_with_clock(
+        &self,
+        release: &str,
+        date: Option<NaiveDate>,
+        default: Option<&str>,
+        clock: &dyn Clock,
+    ) -> Result<Option<String>, Error> {
+        let codename = ResultFormat::Codename;
+        let formatted = match release {
+            "unstable" => self.devel_with_clock(date, codename, clock)?,
+            "testing" => self.testing_with_clock(date, codename, clock)?,
+            "stable" => self.stable_with_clock(date, codename, clock)?,
+            "oldstable" => self.old_with_clock(date, codename, clock)?,
+            _ => return Ok(default.map(str::to_string)),
+        };
+        Ok(formatted.as_str().map(str::to_string))
+    }
+
+    /// Debian's development release is the one before `experimental`, i.e. `sid`
+    fn devel_with_clock(
+        &self,
+        date: Option<NaiveDate>,
+        result: ResultFormat,
+        clock: &dyn Clock,
+    ) -> Result<Formatted<'_>, Error> {
+        let date = date.unwrap_or_else(|| clock.today());
+        let distros = self.devel_candidates(date);
+        match distros.len().checked_sub(2) {
+            Some(index) => Ok(self.format(result, distros[index])),
+            None => bail!(OUTDATED_MSG),
+        }
+    }
+
+    fn valid(&self, codename: &str) -> bool {
+        self.all().contains(&codename) || ALIASES.contains(&codename)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CompatDistroInfo, DebianDistroInfo, Formatted, ResultFormat, UbuntuDistroInfo};
+    use crate::clock::FixedClock;
+    use crate::tests::naive_date;
+
+    fn texts(formatted: Vec<Formatted>) -> Vec<String> {
+        formatted
+            .iter()
+            .map(|formatted| formatted.as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn result_formats() {
+        let ubuntu = UbuntuDistroInfo::new().unwrap();
+        let date = Some(naive_date(2024, 6, 1));
+        let stable = |result: &str| ubuntu.stable(date, result.parse().unwrap()).unwrap();
+        assert_eq!(Some("noble"), stable("codename").as_str());
+        assert_eq!(
+            Some("Ubuntu 24.04 LTS \"Noble Numbat\""),
+            stable("fullname").as_str()
+        );
+        assert_eq!(Some("24.04 LTS"), stable("release").as_str());
+        match stable("object") {
+            Formatted::Object(distro_release) => assert_eq!("noble", distro_release.series()),
+            Formatted::Text(text) => panic!("unexpected text result {}", text),
+        }
+        assert!("series".parse::<ResultFormat>().is_err());
+    }
+
+    #[test]
+    fn ubuntu_methods() {
+        let ubuntu = UbuntuDistroInfo::new().unwrap();
+        let date = Some(naive_date(2024, 6, 1));
+        let codename = ResultFormat::Codename;
+        assert_eq!(
+            Some("oracular"),
+            ubuntu.devel(date, codename).unwrap().as_str()
+        );
+        assert_eq!(Some("noble"), ubuntu.lts(date, codename).unwrap().as_str());
+        assert_eq!(
+            Some("jammy".to_string()),
+            ubuntu.codename("jammy", date, None).unwrap()
+        );
+        assert_eq!(Some("22.04 LTS".to_string()), ubuntu.version("jammy", None));
+        assert_eq!(
+            Some("22.04 LTS".to_string()),
+            ubuntu.version("Jammy Jellyfish", None)
+        );
+        assert_eq!(
+            Some("unknown".to_string()),
+            ubuntu.version("nonexistent", Some("unknown"))
+        );
+        assert!(ubuntu.is_lts("jammy"));
+        assert!(!ubuntu.is_lts("kinetic"));
+        assert!(!ubuntu.is_lts("nonexistent"));
+        assert!(ubuntu.valid("jammy"));
+        assert!(!ubuntu.valid("Jammy Jellyfish"));
+        assert_eq!(
+            vec!["focal", "jammy", "mantic", "noble", "oracular"],
+            texts(ubuntu.supported(date, codename).unwrap())
+        );
+        assert!(texts(ubuntu.unsupported(date, codename).unwrap()).contains(&"lunar".into()));
+        let esm = texts(ubuntu.supported_esm(date, codename).unwrap());
+        assert_eq!(vec!["xenial", "bionic", "focal", "jammy", "noble"], esm);
+        assert_eq!(ubuntu.all().len(), ubuntu.get_all(codename).len());
+    }
+
+    #[test]
+    fn debian_methods() {
+        let debian = DebianDistroInfo::new().unwrap();
+        let date = Some(naive_date(2024, 6, 1));
+        let codename = |alias: &str| debian.codename(alias, date, None).unwrap();
+        assert_eq!(Some("sid".to_string()), codename("unstable"));
+        assert_eq!(Some("trixie".to_string()), codename("testing"));
+        assert_eq!(
+            Some("forky"),
+            debian
+                .testing(Some(naive_date(2026, 10, 18)), ResultFormat::Codename)
+                .unwrap()
+                .as_str()
+        );
+        assert_eq!(Some("bookworm".to_string()), codename("stable"));
+        assert_eq!(Some("bullseye".to_string()), codename("oldstable"));
+        assert_eq!(None, codename("bookworm"));
+        assert_eq!(
+            Some("bookworm".to_string()),
+            debian.codename("bookworm", date, Some("bookworm")).unwrap()
+        );
+        assert!(debian.valid("stable"));
+        assert!(debian.valid("bookworm"));
+        assert!(!debian.valid("jammy"));
+        assert_eq!(Some("12".to_string()), debian.version("bookworm", None));
+        assert_eq!(
+            vec!["buster"],
+            texts(debian.lts_supported(date, ResultFormat::Codename).unwrap())
+        );
+        assert_eq!(
+            vec!["jessie", "stretch"],
+            texts(debian.elts_supported(date, ResultFormat::Codename).unwrap())
+        );
+    }
+
+    #[test]
+    fn clock_defaults() {
+        let clock = FixedClock::new(naive_date(2024, 6, 1));
+        let codename = ResultFormat::Codename;
+        let ubuntu = UbuntuDistroInfo::new().unwrap();
+        assert_eq!(
+            Some("noble"),
+            ubuntu
+                .lts_with_clock(None, codename, &clock)
+                .unwrap()
+                .as_str()
+        );
+        assert_eq!(
+            Some("kinetic"),
+            ubuntu
+                .stable_with_clock(Some(naive_date(2023, 1, 1)), codename, &clock)
+                .unwrap()
+                .as_str()
+        );
+        let debian = DebianDistroInfo::new().unwrap();
+        assert_eq!(
+            Some("trixie".to_string()),
+            debian
+                .codename_with_clock("testing", None, None, &clock)
+                .unwrap()
+        );
+        assert_eq!(
+            Some("sid"),
+            debian
+                .devel_with_clock(None, codename, &clock)
+                .unwrap()
+                .as_str()
+        );
+    }
+}
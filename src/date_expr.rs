@@ -0,0 +1,279 @@
+//! Parse and resolve flexible date expressions, as accepted by `--date`.
+//!
+//! An expression is a base date followed by any number of offsets.  The base is one of:
+//!
+//! * an absolute date in `YYYY-MM-DD` format,
+//! * `today`, `yesterday` or `tomorrow`,
+//! * `next-week`, `next-month`, `next-year` (or `last-...`), relative to today,
+//! * a milestone anchor, `SERIES:MILESTONE` (e.g. `jammy:eol` or `bookworm:release`), or
+//! * nothing at all, meaning today.
+//!
+//! Offsets are a sign, a number and a unit of `d`(ays), `w`(eeks), `m`(onths) or `y`(ears), so
+//! `+90d`, `-2y` and `noble:release+1y` are all valid expressions.
+use std::str::FromStr;
+
+use anyhow::Error;
+use chrono::{Days, Months, NaiveDate};
+
+use crate::clock::Clock;
+use crate::suggest::UnknownRelease;
+use crate::{DistroInfo, DistroRelease, Milestone};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DateUnit {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DateBase {
+    Today,
+    Date(NaiveDate),
+    Anchor { series: String, milestone: String },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DateExpr {
+    base: DateBase,
+    offsets: Vec<(i64, DateUnit)>,
+}
+
+/// Returns the date of the named milestone for `distro_release`
+///
+/// As well as the EOL milestones, this accepts `created`, `release` and the names of the release's
+/// extra fields.
+pub fn named_milestone_date(
+    distro_release: &DistroRelease,
+    name: &str,
+) -> Result<Option<NaiveDate>, Error> {
+    Ok(match name {
+        "created" => *distro_release.created(),
+        "release" => *distro_release.release(),
+        _ => match name.parse::<Milestone>() {
+            Ok(milestone) => distro_release.milestone_date(&milestone),
+            Err(error) => match distro_release.extra_field(name) {
+                Some(value) => {
+                    Some(NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+                        format_err!("`{}' of {} is not a date", name, distro_release.series())
+                    })?)
+                }
+                None => return Err(error),
+            },
+        },
+    })
+}
+
+fn apply_offset(date: NaiveDate, amount: i64, unit: &DateUnit) -> Option<NaiveDate> {
+    let months = match unit {
+        DateUnit::Day => return add_days(date, amount),
+        DateUnit::Week => return add_days(date, amount * 7),
+        DateUnit::Month => amount,
+        DateUnit::Year => amount * 12,
+    };
+    let delta = Months::new(u32::try_from(months.unsigned_abs()).ok()?);
+    if months < 0 {
+        date.checked_sub_months(delta)
+    } else {
+        date.checked_add_months(delta)
+    }
+}
+
+fn add_days(date: NaiveDate, amount: i64) -> Option<NaiveDate> {
+    let delta = Days::new(amount.unsigned_abs());
+    if amount < 0 {
+        date.checked_sub_days(delta)
+    } else {
+        date.checked_add_days(delta)
+    }
+}
+
+/// Split a trailing offset (e.g. `+90d`) from the end of `value`, if it has one
+fn split_offset(value: &str) -> Option<(&str, (i64, DateUnit))> {
+    let unit = match value.chars().last()? {
+        'd' => DateUnit::Day,
+        'w' => DateUnit::Week,
+        'm' => DateUnit::Month,
+        'y' => DateUnit::Year,
+        _ => return None,
+    };
+    let rest = &value[..value.len() - 1];
+    let digits_start = rest.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    if digits_start == rest.len() || digits_start == 0 {
+        return None;
+    }
+    let sign = match &rest[digits_start - 1..digits_start] {
+        "+" => 1,
+        "-" => -1,
+        _ => return None,
+    };
+    let amount: i64 = rest[digits_start..].parse().ok()?;
+    Some((&rest[..digits_start - 1], (sign * amount, unit)))
+}
+
+impl FromStr for DateExpr {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Error> {
+        let mut rest = value.trim();
+        let mut offsets = vec![];
+        while let Some((remainder, offset)) = split_offset(rest) {
+            offsets.insert(0, offset);
+            rest = remainder;
+        }
+        let relative = |amount, unit| (DateBase::Today, Some((amount, unit)));
+        let (base, implied_offset) = match rest {
+            "" | "today" => (DateBase::Today, None),
+            "yesterday" => relative(-1, DateUnit::Day),
+            "tomorrow" => relative(1, DateUnit::Day),
+            "next-week" => relative(1, DateUnit::Week),
+            "next-month" => relative(1, DateUnit::Month),
+            "next-year" => relative(1, DateUnit::Year),
+            "last-week" => relative(-1, DateUnit::Week),
+            "last-month" => relative(-1, DateUnit::Month),
+            "last-year" => relative(-1, DateUnit::Year),
+            _ => match rest.split_once(':') {
+                Some((series, milestone)) if !series.is_empty() && !milestone.is_empty() => (
+                    DateBase::Anchor {
+                        series: series.to_string(),
+                        milestone: milestone.to_string(),
+                    },
+                    None,
+                ),
+                _ => (
+                    DateBase::Date(NaiveDate::parse_from_str(rest, "%Y-%m-%d").map_err(|_| {
+                        format_err!(
+                            "Failed to parse date '{}'; must be YYYY-MM-DD format, a relative \
+                             expression such as +90d, or SERIES:MILESTONE",
+                            value
+                        )
+                    })?),
+                    None,
+                ),
+            },
+        };
+        if let Some(offset) = implied_offset {
+            offsets.insert(0, offset);
+        }
+        Ok(Self { base, offsets })
+    }
+}
+
+impl From<NaiveDate> for DateExpr {
+    fn from(date: NaiveDate) -> Self {
+        Self {
+            base: DateBase::Date(date),
+            offsets: vec![],
+        }
+    }
+}
+
+impl DateExpr {
+    pub fn base(&self) -> &DateBase {
+        &self.base
+    }
+
+    /// Resolve this expression to a date, looking up any milestone anchor in `distro_info` and
+    /// using `clock` as the base for relative expressions
+    pub fn resolve(
+        &self,
+        distro_info: &impl DistroInfo,
+        clock: &dyn Clock,
+    ) -> Result<NaiveDate, Error> {
+        let mut date = match &self.base {
+            DateBase::Today => clock.today(),
+            DateBase::Date(date) => *date,
+            DateBase::Anchor { series, milestone } => {
+                let distro_release = distro_info
+                    .iter()
+                    .find(|distro_release| distro_release.series() == series)
+                    .ok_or_else(|| UnknownRelease::new(distro_info, "series", series))?;
+                named_milestone_date(distro_release, milestone)?
+                    .ok_or_else(|| format_err!("{} has no {} date", series, milestone))?
+            }
+        };
+        for (amount, unit) in &self.offsets {
+            date = apply_offset(date, *amount, unit)
+                .ok_or_else(|| format_err!("date expression out of range"))?;
+        }
+        Ok(date)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DateBase, DateExpr, DateUnit};
+    use crate::clock::FixedClock;
+    use crate::tests::naive_date;
+    use crate::{DistroInfo, UbuntuDistroInfo};
+
+    #[test]
+    fn date_expr_parse() {
+        let parse = |value: &str| value.parse::<DateExpr>().unwrap();
+        assert_eq!(DateExpr::from(naive_date(2024, 1, 31)), parse("2024-01-31"));
+        assert_eq!(DateBase::Today, *parse("today").base());
+        assert_eq!(vec![(90, DateUnit::Day)], parse("+90d").offsets);
+        assert_eq!(vec![(-2, DateUnit::Year)], parse("-2y").offsets);
+        assert_eq!(vec![(1, DateUnit::Month)], parse("next-month").offsets);
+        assert_eq!(
+            vec![(1, DateUnit::Year), (-3, DateUnit::Week)],
+            parse("2024-01-31+1y-3w").offsets
+        );
+        let anchor = parse("jammy:eol-lts+1y");
+        assert_eq!(
+            DateBase::Anchor {
+                series: "jammy".to_string(),
+                milestone: "eol-lts".to_string()
+            },
+            *anchor.base()
+        );
+        assert_eq!(vec![(1, DateUnit::Year)], anchor.offsets);
+    }
+
+    #[test]
+    fn date_expr_parse_invalid() {
+        for value in ["2024-13-01", "soon", "+d", "90d", "jammy:"] {
+            assert!(value.parse::<DateExpr>().is_err(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn date_expr_resolve_relative() {
+        let ubuntu_distro_info = UbuntuDistroInfo::from_vec(vec![]);
+        let clock = FixedClock::new(naive_date(2024, 1, 31));
+        let resolve = |value: &str| {
+            value
+                .parse::<DateExpr>()
+                .unwrap()
+                .resolve(&ubuntu_distro_info, &clock)
+                .unwrap()
+        };
+        assert_eq!(naive_date(2024, 1, 31), resolve("today"));
+        assert_eq!(naive_date(2024, 4, 30), resolve("+90d"));
+        assert_eq!(naive_date(2022, 1, 31), resolve("-2y"));
+        // Month arithmetic clamps to the end of shorter months
+        assert_eq!(naive_date(2024, 2, 29), resolve("next-month"));
+        assert_eq!(naive_date(2024, 1, 30), resolve("yesterday"));
+    }
+
+    #[test]
+    fn date_expr_resolve_anchor() {
+        let ubuntu_distro_info = UbuntuDistroInfo::new().unwrap();
+        let clock = FixedClock::new(naive_date(2024, 1, 31));
+        let resolve = |value: &str| {
+            value
+                .parse::<DateExpr>()
+                .unwrap()
+                .resolve(&ubuntu_distro_info, &clock)
+        };
+        assert_eq!(
+            naive_date(2025, 4, 25),
+            resolve("noble:release+1y").unwrap()
+        );
+        assert_eq!(naive_date(2006, 4, 30), resolve("warty:eol").unwrap());
+        assert!(resolve("foobar:eol").is_err());
+        assert!(resolve("warty:eol-esm").is_err());
+        assert!(resolve("warty:bedtime").is_err());
+    }
+}
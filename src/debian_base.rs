@@ -0,0 +1,145 @@
+//! Infer which Debian release each Ubuntu release was based on.
+//!
+//! An Ubuntu release imports packages from Debian while it is open for development, so its base is
+//! taken to be the Debian release that was testing when the Ubuntu release was created (or, if
+//! Debian had no testing release then, the latest stable release).  Known exceptions can be given
+//! as overrides, mapping Ubuntu series to Debian series.
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::Error;
+use csv::ReaderBuilder;
+
+use crate::{DebianDistroInfo, DistroInfo, DistroRelease, UbuntuDistroInfo};
+
+/// The Debian release an Ubuntu release was based on
+#[derive(Clone, Debug, PartialEq)]
+pub struct DebianBase<'u, 'd> {
+    pub ubuntu: &'u DistroRelease,
+    /// `None` if the Ubuntu release has no creation date, or predates the Debian data
+    pub debian: Option<&'d DistroRelease>,
+    /// Whether `debian` came from the overrides rather than being inferred
+    pub overridden: bool,
+}
+
+/// Returns the Debian release that was testing (or the latest stable) when `ubuntu` was created
+pub fn inferred_debian_base<'d>(
+    ubuntu: &DistroRelease,
+    debian_distro_info: &'d DebianDistroInfo,
+) -> Option<&'d DistroRelease> {
+    let created = (*ubuntu.created())?;
+    debian_distro_info
+        .testing(created)
+        .or_else(|| debian_distro_info.stable(created))
+}
+
+/// Returns the Debian base of every Ubuntu release, applying `overrides` (Ubuntu series to Debian
+/// series)
+pub fn debian_bases<'u, 'd>(
+    ubuntu_distro_info: &'u UbuntuDistroInfo,
+    debian_distro_info: &'d DebianDistroInfo,
+    overrides: &HashMap<String, String>,
+) -> Result<Vec<DebianBase<'u, 'd>>, Error> {
+    for ubuntu_series in overrides.keys() {
+        if !ubuntu_distro_info
+            .iter()
+            .any(|distro_release| distro_release.series() == ubuntu_series)
+        {
+            bail!("unknown distribution series `{}'", ubuntu_series);
+        }
+    }
+    ubuntu_distro_info
+        .iter()
+        .map(|ubuntu| {
+            Ok(match overrides.get(ubuntu.series()) {
+                Some(debian_series) => DebianBase {
+                    ubuntu,
+                    debian: Some(
+                        debian_distro_info
+                            .iter()
+                            .find(|distro_release| distro_release.series() == debian_series)
+                            .ok_or_else(|| {
+                                format_err!("unknown distribution series `{}'", debian_series)
+                            })?,
+                    ),
+                    overridden: true,
+                },
+                None => DebianBase {
+                    ubuntu,
+                    debian: inferred_debian_base(ubuntu, debian_distro_info),
+                    overridden: false,
+                },
+            })
+        })
+        .collect()
+}
+
+/// Read overrides from a CSV file with `ubuntu` and `debian` columns of series
+pub fn read_overrides<P: AsRef<Path>>(path: P) -> Result<HashMap<String, String>, Error> {
+    let mut rdr = ReaderBuilder::new().has_headers(true).from_path(path)?;
+    let columns = rdr.headers()?.clone();
+    let column = |name: &str| {
+        columns
+            .iter()
+            .position(|header| header == name)
+            .ok_or_else(|| format_err!("no `{}' column in overrides", name))
+    };
+    let (ubuntu_column, debian_column) = (column("ubuntu")?, column("debian")?);
+    let mut overrides = HashMap::new();
+    for record in rdr.records() {
+        let record = record?;
+        match (record.get(ubuntu_column), record.get(debian_column)) {
+            (Some(ubuntu), Some(debian)) => {
+                overrides.insert(ubuntu.to_string(), debian.to_string());
+            }
+            _ => bail!("incomplete override record: {:?}", record),
+        }
+    }
+    Ok(overrides)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::debian_bases;
+    use crate::{DebianDistroInfo, DistroInfo, UbuntuDistroInfo};
+
+    #[test]
+    fn inferred_and_overridden_bases() {
+        let ubuntu_distro_info = UbuntuDistroInfo::new().unwrap();
+        let debian_distro_info = DebianDistroInfo::new().unwrap();
+        let overrides: HashMap<String, String> = [("noble".to_string(), "sid".to_string())]
+            .into_iter()
+            .collect();
+        let bases = debian_bases(&ubuntu_distro_info, &debian_distro_info, &overrides).unwrap();
+        assert_eq!(ubuntu_distro_info.iter().count(), bases.len());
+        let base = |series: &str| {
+            let base = bases
+                .iter()
+                .find(|base| base.ubuntu.series() == series)
+                .unwrap();
+            (
+                base.debian.map(|debian| debian.series().as_str()),
+                base.overridden,
+            )
+        };
+        assert_eq!((Some("sarge"), false), base("warty"));
+        assert_eq!((Some("bookworm"), false), base("jammy"));
+        assert_eq!((Some("bookworm"), false), base("mantic"));
+        assert_eq!((Some("trixie"), false), base("questing"));
+        assert_eq!((Some("sid"), true), base("noble"));
+    }
+
+    #[test]
+    fn unknown_overrides() {
+        let ubuntu_distro_info = UbuntuDistroInfo::new().unwrap();
+        let debian_distro_info = DebianDistroInfo::new().unwrap();
+        for (ubuntu, debian) in [("nonexistent", "bookworm"), ("noble", "nonexistent")] {
+            let overrides: HashMap<String, String> = [(ubuntu.to_string(), debian.to_string())]
+                .into_iter()
+                .collect();
+            assert!(debian_bases(&ubuntu_distro_info, &debian_distro_info, &overrides).is_err());
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! A detailed description of a single release, as printed by `--show`.
+use std::fmt;
+
+use chrono::NaiveDate;
+
+use crate::{alias_for, Distro, DistroInfo, DistroRelease, Milestone};
+
+/// The width of the label column
+const LABEL_WIDTH: usize = 13;
+
+/// Every field of a release, with its support phase, the days to each of its milestones, its
+/// Debian alias and its neighbouring releases at a given date
+///
+/// Its `Display` implementation renders a multi-line report.
+pub struct ReleaseDetail<'a, T: DistroInfo> {
+    distro_info: &'a T,
+    distro_release: &'a DistroRelease,
+    date: NaiveDate,
+}
+
+impl<'a, T: DistroInfo> ReleaseDetail<'a, T> {
+    pub fn new(distro_info: &'a T, distro_release: &'a DistroRelease, date: NaiveDate) -> Self {
+        Self {
+            distro_info,
+            distro_release,
+            date,
+        }
+    }
+
+    /// A description of where the release is in its lifecycle at the date
+    pub fn phase(&self) -> String {
+        let distro_release = self.distro_release;
+        if !distro_release.created_at(self.date) {
+            "not yet created".to_string()
+        } else if !distro_release.released_at(self.date) {
+            "in development".to_string()
+        } else {
+            match distro_release.phase_at(self.date) {
+                Some(phase) => format!("{} support", phase.name()),
+                None => "end of life".to_string(),
+            }
+        }
+    }
+
+    /// The releases before and after this one in release order (see `cmp_by_release`), if any
+    ///
+    /// Releases without a version (Debian's sid and experimental) are outside the chain of
+    /// releases, so have no neighbours and are no release's neighbour.
+    pub fn neighbours(&self) -> (Option<&'a DistroRelease>, Option<&'a DistroRelease>) {
+        if self.distro_release.version().is_none() {
+            return (None, None);
+        }
+        let mut releases: Vec<&'a DistroRelease> = self
+            .distro_info
+            .iter()
+            .filter(|distro_release| distro_release.version().is_some())
+            .collect();
+        releases.sort_by(|a, b| a.cmp_by_release(b));
+        match releases
+            .iter()
+            .position(|distro_release| std::ptr::eq(*distro_release, self.distro_release))
+        {
+            Some(index) => (
+                index.checked_sub(1).map(|index| releases[index]),
+                releases.get(index + 1).copied(),
+            ),
+            None => (None, None),
+        }
+    }
+
+    /// The release's milestones and their dates, in lifecycle order, followed by any date-valued
+    /// extra fields
+    pub fn milestones(&self) -> Vec<(String, NaiveDate)> {
+        let distro_release = self.distro_release;
+        let mut milestones: Vec<(String, NaiveDate)> = [
+            ("created", *distro_release.created()),
+            ("release", *distro_release.release()),
+            ("eol", *distro_release.eol()),
+            ("eol-lts", *distro_release.eol_lts()),
+            ("eol-elts", *distro_release.eol_elts()),
+            ("eol-server", *distro_release.eol_server()),
+            ("eol-esm", *distro_release.eol_esm()),
+        ]
+        .into_iter()
+        .filter_map(|(name, date)| Some((name.to_string(), date?)))
+        .collect();
+        for name in distro_release.extra_fields().keys() {
+            if let Some(date) = distro_release.milestone_date(&Milestone::Custom(name.clone())) {
+                milestones.push((name.clone(), date));
+            }
+        }
+        milestones
+    }
+}
+
+/// Describe the distance from `date` to `target`
+fn days_until(date: NaiveDate, target: NaiveDate) -> String {
+    match (target - date).num_days() {
+        0 => "today".to_string(),
+        1 => "in 1 day".to_string(),
+        -1 => "1 day ago".to_string(),
+        days if days > 0 => format!("in {} days", days),
+        days => format!("{} days ago", -days),
+    }
+}
+
+fn field(f: &mut fmt::Formatter<'_>, label: &str, value: &str) -> fmt::Result {
+    writeln!(
+        f,
+        "{:width$}{}",
+        format!("{}:", label),
+        value,
+        width = LABEL_WIDTH
+    )
+}
+
+impl<T: DistroInfo> fmt::Display for ReleaseDetail<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let distro_release = self.distro_release;
+        let series = distro_release.series();
+        let none = "(none)".to_string();
+        field(
+            f,
+            "version",
+            distro_release.version().as_ref().unwrap_or(&none),
+        )?;
+        field(f, "codename", distro_release.codename())?;
+        field(f, "series", series)?;
+        if self.distro_info.distro() == &Distro::Debian {
+            let alias = alias_for(self.distro_info, series, self.date);
+            field(f, "alias", if alias == series { &none } else { alias })?;
+        }
+        field(f, "phase", &format!("{} on {}", self.phase(), self.date))?;
+        let (predecessor, successor) = self.neighbours();
+        field(
+            f,
+            "predecessor",
+            predecessor.map_or(&none, |dr| dr.series()),
+        )?;
+        field(f, "successor", successor.map_or(&none, |dr| dr.series()))?;
+        writeln!(f, "milestones:")?;
+        let milestones = self.milestones();
+        let name_width = milestones
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or_default();
+        for (name, date) in milestones {
+            writeln!(
+                f,
+                "  {:name_width$}  {}  ({})",
+                name,
+                date,
+                days_until(self.date, date)
+            )?;
+        }
+        let other_fields: Vec<(&String, &String)> = distro_release
+            .extra_fields()
+            .iter()
+            .filter(|(name, _)| {
+                distro_release
+                    .milestone_date(&Milestone::Custom(name.to_string()))
+                    .is_none()
+            })
+            .collect();
+        if !other_fields.is_empty() {
+            writeln!(f, "other fields:")?;
+            for (name, value) in other_fields {
+                writeln!(f, "  {}: {}", name, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::ReleaseDetail;
+    use crate::{DebianDistroInfo, DistroInfo, UbuntuDistroInfo};
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 6, 1).unwrap()
+    }
+
+    #[test]
+    fn ubuntu_detail() {
+        let ubuntu = UbuntuDistroInfo::new().unwrap();
+        let jammy = ubuntu.iter().find(|dr| dr.series() == "jammy").unwrap();
+        assert_eq!(
+            "version:     22.04 LTS
+codename:    Jammy Jellyfish
+series:      jammy
+phase:       standard support on 2024-06-01
+predecessor: impish
+successor:   kinetic
+milestones:
+  created     2021-10-14  (961 days ago)
+  release     2022-04-21  (772 days ago)
+  eol         2027-06-01  (in 1095 days)
+  eol-server  2027-06-01  (in 1095 days)
+  eol-esm     2032-04-21  (in 2881 days)
+  eol-legacy  2034-04-25  (in 3615 days)
+",
+            ReleaseDetail::new(&ubuntu, jammy, date()).to_string()
+        );
+    }
+
+    #[test]
+    fn debian_detail() {
+        let debian = DebianDistroInfo::new().unwrap();
+        let detail = |series: &str| {
+            let distro_release = debian.iter().find(|dr| dr.series() == series).unwrap();
+            ReleaseDetail::new(&debian, distro_release, date())
+        };
+        let bookworm = detail("bookworm").to_string();
+        assert!(bookworm.contains("\nalias:       stable\n"));
+        assert!(bookworm.contains("\npredecessor: bullseye\nsuccessor:   trixie\n"));
+        assert_eq!("in development", detail("trixie").phase());
+        assert_eq!("LTS support", detail("buster").phase());
+        assert_eq!("end of life", detail("hamm").phase());
+        assert!(detail("hamm")
+            .to_string()
+            .contains("\nalias:       (none)\n"));
+        let neighbours = |series: &str| {
+            let (predecessor, successor) = detail(series).neighbours();
+            (
+                predecessor.map(|dr| dr.series().to_string()),
+                successor.map(|dr| dr.series().to_string()),
+            )
+        };
+        assert_eq!((None, Some("rex".to_string())), neighbours("buzz"));
+        assert_eq!(
+            (Some("trixie".to_string()), Some("duke".to_string())),
+            neighbours("forky")
+        );
+        assert_eq!((None, None), neighbours("sid"));
+        assert_eq!((None, None), neighbours("experimental"));
+        assert!(detail("sid")
+            .to_string()
+            .contains("\npredecessor: (none)\nsuccessor:   (none)\n"));
+    }
+}
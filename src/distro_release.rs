@@ -1,9 +1,22 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+
 use chrono::naive::NaiveDate;
 
-use crate::Milestone;
+use crate::{Distro, Milestone, Phase};
 
+/// A release of a distro
+///
+/// Releases are equal, and hash, by their distro and series alone, so the same series from
+/// different sources (e.g. amended by an overlay) is deduplicated.  There is no `Ord`, as no
+/// chronological order could agree with that equality; sort with `cmp_by_release`,
+/// `cmp_by_created` or `cmp_by_milestone` instead.
 #[derive(Default, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct DistroRelease {
+    #[cfg_attr(feature = "serde", serde(skip))]
+    distro: Option<Distro>,
     version: Option<String>,
     codename: String,
     series: String,
@@ -14,9 +27,20 @@ pub struct DistroRelease {
     eol_elts: Option<NaiveDate>,
     eol_esm: Option<NaiveDate>,
     eol_server: Option<NaiveDate>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "BTreeMap::is_empty"))]
+    extra_fields: BTreeMap<String, String>,
+}
+
+/// A contiguous period of a release's support lifecycle
+#[derive(Clone, Debug, PartialEq)]
+pub struct SupportSegment {
+    pub phase: Phase,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
 }
 
 impl DistroRelease {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         version: String,
         codename: String,
@@ -44,10 +68,27 @@ impl DistroRelease {
             eol_elts,
             eol_esm,
             eol_server,
+            extra_fields: BTreeMap::new(),
+            distro: None,
         }
     }
 
+    /// Set the distro this release belongs to (as `DistroInfo::from_vec` does)
+    pub fn with_distro(mut self, distro: Distro) -> Self {
+        self.distro = Some(distro);
+        self
+    }
+
+    /// Attach fields from columns beyond those distro-info-data defines
+    pub fn with_extra_fields(mut self, extra_fields: BTreeMap<String, String>) -> Self {
+        self.extra_fields = extra_fields;
+        self
+    }
+
     // Getters
+    pub fn distro(&self) -> &Option<Distro> {
+        &self.distro
+    }
     pub fn version(&self) -> &Option<String> {
         &self.version
     }
@@ -78,6 +119,12 @@ impl DistroRelease {
     pub fn eol_lts(&self) -> &Option<NaiveDate> {
         &self.eol_lts
     }
+    pub fn extra_fields(&self) -> &BTreeMap<String, String> {
+        &self.extra_fields
+    }
+    pub fn extra_field(&self, name: &str) -> Option<&str> {
+        self.extra_fields.get(name).map(|value| value.as_str())
+    }
 
     // Non-getters
     pub fn ubuntu_is_lts(&self) -> bool {
@@ -108,6 +155,12 @@ impl DistroRelease {
             Milestone::EolESM => self.eol_esm(),
             Milestone::EolLTS => self.eol_lts(),
             Milestone::EolServer => self.eol_server(),
+            // Extra fields which aren't dates don't mark a milestone
+            Milestone::Custom(name) => {
+                return self
+                    .extra_field(name)
+                    .and_then(|value| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok())
+            }
         }
     }
 
@@ -120,6 +173,77 @@ impl DistroRelease {
             }
     }
 
+    /// Compares two releases chronologically, for sorting
+    ///
+    /// Releases are ordered by release date, with releases which have not yet been given one last
+    /// (ordered by creation date), and ties broken by distro and series.
+    pub fn cmp_by_release(&self, other: &Self) -> Ordering {
+        cmp_dates(self.release, other.release)
+            .then_with(|| cmp_dates(self.created, other.created))
+            .then_with(|| self.distro.cmp(&other.distro))
+            .then_with(|| self.series.cmp(&other.series))
+    }
+
+    /// Compares the creation dates of two releases, as `cmp_by_milestone` does
+    pub fn cmp_by_created(&self, other: &Self) -> Ordering {
+        cmp_dates(self.created, other.created).then_with(|| self.cmp_by_release(other))
+    }
+
+    /// Compares the dates of `milestone` of two releases, for sorting
+    ///
+    /// Releases without the milestone sort last, and ties are broken by `cmp_by_release`.
+    pub fn cmp_by_milestone(&self, other: &Self, milestone: &Milestone) -> Ordering {
+        cmp_dates(
+            self.milestone_date(milestone),
+            other.milestone_date(milestone),
+        )
+        .then_with(|| self.cmp_by_release(other))
+    }
+
+    /// Returns the phases of this release's support lifecycle, in order, running from its release
+    /// date to its final EOL milestone
+    ///
+    /// Releases without a release date or EOL date have no segments.
+    pub fn support_segments(&self) -> Vec<SupportSegment> {
+        let mut segments = vec![];
+        let (mut start, standard_end) = match (self.release, self.eol) {
+            (Some(release), Some(eol)) => (release, ::std::cmp::max(Some(eol), self.eol_server)),
+            _ => return segments,
+        };
+        for (phase, end) in [
+            (Phase::Standard, standard_end),
+            (Phase::LTS, self.eol_lts),
+            (Phase::ELTS, self.eol_elts),
+            (Phase::ESM, self.eol_esm),
+        ] {
+            if let Some(end) = end {
+                if end > start {
+                    segments.push(SupportSegment { phase, start, end });
+                    start = end;
+                }
+            }
+        }
+        segments
+    }
+
+    /// Returns the support phase this release is in at the given date, or `None` if it is
+    /// unreleased or past its final EOL milestone
+    ///
+    /// Released versions without an EOL date are in standard support.
+    pub fn phase_at(&self, date: NaiveDate) -> Option<Phase> {
+        if !self.released_at(date) {
+            return None;
+        }
+        let segments = self.support_segments();
+        if segments.is_empty() {
+            return Some(Phase::Standard);
+        }
+        segments
+            .into_iter()
+            .find(|segment| date >= segment.start && date <= segment.end)
+            .map(|segment| segment.phase)
+    }
+
     pub fn ubuntu_supported_at(&self, date: NaiveDate) -> bool {
         self.created_at(date)
             && match self.eol {
@@ -132,15 +256,47 @@ impl DistroRelease {
     }
 }
 
+/// Compares two optional dates, with missing dates last
+fn cmp_dates(a: Option<NaiveDate>, b: Option<NaiveDate>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+impl PartialEq for DistroRelease {
+    fn eq(&self, other: &Self) -> bool {
+        self.distro == other.distro && self.series == other.series
+    }
+}
+
+impl Eq for DistroRelease {}
+
+impl Hash for DistroRelease {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.distro.hash(state);
+        self.series.hash(state);
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::DistroRelease;
+    use std::cmp::Ordering;
+    use std::collections::HashSet;
+
+    use chrono::NaiveDate;
 
-    use crate::{tests::naive_date, Milestone};
+    use super::{DistroRelease, SupportSegment};
+
+    use crate::{
+        tests::naive_date, DebianDistroInfo, Distro, DistroInfo, Milestone, Phase, UbuntuDistroInfo,
+    };
 
     #[test]
     fn create_struct() {
-        DistroRelease {
+        let _ = DistroRelease {
             version: Some("version".to_string()),
             codename: "codename".to_string(),
             series: "series".to_string(),
@@ -392,4 +548,196 @@ mod tests {
         assert!(!distro_release.supported_at(naive_date(2018, 1, 1), &Milestone::EolLTS));
         assert!(distro_release.ubuntu_supported_at(naive_date(2018, 1, 1)));
     }
+
+    #[test]
+    fn distro_release_support_segments() {
+        let distro_release = DistroRelease::new(
+            "98.04 LTS".to_string(),
+            "codename".to_string(),
+            "series".to_string(),
+            Some(naive_date(2018, 1, 1)),
+            Some(naive_date(2018, 6, 14)),
+            Some(naive_date(2020, 6, 14)),
+            None,
+            None,
+            Some(naive_date(2025, 6, 14)),
+            Some(naive_date(2021, 6, 14)),
+        );
+        assert_eq!(
+            vec![
+                SupportSegment {
+                    phase: Phase::Standard,
+                    start: naive_date(2018, 6, 14),
+                    end: naive_date(2021, 6, 14),
+                },
+                SupportSegment {
+                    phase: Phase::ESM,
+                    start: naive_date(2021, 6, 14),
+                    end: naive_date(2025, 6, 14),
+                },
+            ],
+            distro_release.support_segments()
+        );
+    }
+
+    #[test]
+    fn distro_release_support_segments_unreleased() {
+        let distro_release = DistroRelease::new(
+            "".to_string(),
+            "Sid".to_string(),
+            "sid".to_string(),
+            Some(naive_date(1993, 8, 16)),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        assert!(distro_release.support_segments().is_empty());
+    }
+
+    #[test]
+    fn distro_release_phase_at() {
+        let distro_release = DistroRelease::new(
+            "9".to_string(),
+            "Stretch".to_string(),
+            "stretch".to_string(),
+            Some(naive_date(2015, 4, 26)),
+            Some(naive_date(2017, 6, 17)),
+            Some(naive_date(2020, 7, 18)),
+            Some(naive_date(2022, 6, 30)),
+            Some(naive_date(2027, 6, 30)),
+            None,
+            None,
+        );
+        assert_eq!(None, distro_release.phase_at(naive_date(2017, 6, 16)));
+        assert_eq!(
+            Some(Phase::Standard),
+            distro_release.phase_at(naive_date(2017, 6, 17))
+        );
+        // EOL day is still in standard support
+        assert_eq!(
+            Some(Phase::Standard),
+            distro_release.phase_at(naive_date(2020, 7, 18))
+        );
+        assert_eq!(
+            Some(Phase::LTS),
+            distro_release.phase_at(naive_date(2020, 7, 19))
+        );
+        assert_eq!(
+            Some(Phase::ELTS),
+            distro_release.phase_at(naive_date(2027, 6, 30))
+        );
+        assert_eq!(None, distro_release.phase_at(naive_date(2027, 7, 1)));
+    }
+
+    #[test]
+    fn distro_release_phase_at_without_eol() {
+        let distro_release = DistroRelease::new(
+            "14".to_string(),
+            "Forky".to_string(),
+            "forky".to_string(),
+            Some(naive_date(2025, 8, 9)),
+            Some(naive_date(2027, 6, 1)),
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        assert_eq!(
+            Some(Phase::Standard),
+            distro_release.phase_at(naive_date(2040, 1, 1))
+        );
+    }
+
+    #[test]
+    fn equality_and_hashing() {
+        let ubuntu_distro_info = UbuntuDistroInfo::new().unwrap();
+        let jammy = ubuntu_distro_info
+            .iter()
+            .find(|distro_release| distro_release.series() == "jammy")
+            .unwrap();
+        assert_eq!(&Some(Distro::Ubuntu), jammy.distro());
+        // Fields other than the distro and series don't matter
+        let mut amended = jammy.clone().with_extra_fields(
+            [("eol-contract".to_string(), "2034-01-01".to_string())]
+                .into_iter()
+                .collect(),
+        );
+        assert_eq!(jammy, &amended);
+        amended = amended.with_distro(Distro::Debian);
+        assert_ne!(jammy, &amended);
+        // Including its dates, so an amended copy of a series deduplicates with the original
+        let mut redated = jammy.clone();
+        redated.release = Some(naive_date(2022, 4, 22));
+        assert_eq!(jammy, &redated);
+        assert_eq!(jammy.cmp_by_release(&redated), Ordering::Less);
+        assert_eq!(
+            1,
+            [jammy, &redated].into_iter().collect::<HashSet<_>>().len()
+        );
+
+        let mut set: HashSet<&DistroRelease> = ubuntu_distro_info.iter().collect();
+        let count = set.len();
+        assert_eq!(ubuntu_distro_info.iter().count(), count);
+        let ubuntu_again = UbuntuDistroInfo::new().unwrap();
+        set.extend(ubuntu_again.iter());
+        assert_eq!(count, set.len());
+        let debian_distro_info = DebianDistroInfo::new().unwrap();
+        set.extend(debian_distro_info.iter());
+        assert_eq!(count + debian_distro_info.iter().count(), set.len());
+    }
+
+    #[test]
+    fn ordering() {
+        let debian_distro_info = DebianDistroInfo::new().unwrap();
+        let mut distro_releases: Vec<_> = debian_distro_info.iter().rev().collect();
+        distro_releases.sort_by(|a, b| a.cmp_by_release(b));
+        let series: Vec<_> = distro_releases
+            .iter()
+            .map(|distro_release| distro_release.series().as_str())
+            .collect();
+        assert_eq!(["buzz", "rex", "bo"], series[..3]);
+        // Undated releases come last, ordered by creation date then series
+        let undated = series.len() - 4;
+        assert_eq!(["experimental", "sid", "forky", "duke"], series[undated..]);
+        assert!(distro_releases[..undated]
+            .windows(2)
+            .all(|pair| pair[0].release() <= pair[1].release()));
+    }
+
+    #[test]
+    fn cmp_by_milestone() {
+        let release =
+            |series: &str, eol: Option<NaiveDate>, eol_lts: Option<NaiveDate>| DistroRelease {
+                series: series.to_string(),
+                release: Some(naive_date(2020, 1, 1)),
+                eol,
+                eol_lts,
+                ..Default::default()
+            };
+        let mut distro_releases = [
+            release("a", Some(naive_date(2023, 1, 1)), None),
+            release(
+                "b",
+                Some(naive_date(2022, 1, 1)),
+                Some(naive_date(2030, 1, 1)),
+            ),
+            release("c", None, Some(naive_date(2028, 1, 1))),
+        ];
+        let series = |distro_releases: &[DistroRelease]| -> String {
+            distro_releases
+                .iter()
+                .map(|distro_release| distro_release.series().as_str())
+                .collect()
+        };
+        distro_releases.sort_by(|a, b| a.cmp_by_milestone(b, &Milestone::Eol));
+        assert_eq!("bac", series(&distro_releases));
+        distro_releases.sort_by(|a, b| a.cmp_by_milestone(b, &Milestone::EolLTS));
+        assert_eq!("cba", series(&distro_releases));
+        distro_releases.sort_by(|a, b| a.cmp_by_created(b));
+        assert_eq!("abc", series(&distro_releases));
+    }
 }
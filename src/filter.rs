@@ -0,0 +1,475 @@
+//! A small expression language for filtering releases.
+//!
+//! Expressions combine predicates and comparisons with `&&`, `||`, `!` and parentheses, e.g.
+//! `lts && eol_esm > 2030-01-01 && release < noble.release`.
+//!
+//! * Predicates: `lts`, `released`, `supported`, `unsupported` and `devel`.  Support is judged
+//!   by the same rules as `--supported`, and `lts` follows `--lts`: an Ubuntu LTS release, or a
+//!   Debian release past its EOL but still in LTS support.
+//! * Date fields: `created`, `release` and the EOL milestones (`eol`, `eol_lts`, `eol_elts`,
+//!   `eol_esm`, `eol_server`; hyphens may be used instead of underscores).  These can be compared
+//!   with `<`, `<=`, `>`, `>=`, `==` and `!=` against another date field, a milestone of another
+//!   release (`SERIES.MILESTONE`), or anything `--date` accepts (e.g. `2030-01-01` or `+1y`,
+//!   relative to the evaluation date).  Comparisons involving a missing date are false.
+//! * Text fields: `series`, `codename` and `version`, compared with `==` or `!=` against a word
+//!   or a double-quoted string.
+use std::str::FromStr;
+
+use anyhow::Error;
+use chrono::NaiveDate;
+
+use crate::clock::FixedClock;
+use crate::date_expr::{named_milestone_date, DateExpr};
+use crate::{Distro, DistroInfo, DistroRelease, Milestone};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+    Op(CmpOp),
+    Word(String),
+    Quoted(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CmpOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl CmpOp {
+    fn compare<T: PartialOrd>(&self, lhs: T, rhs: T) -> bool {
+        match self {
+            CmpOp::Lt => lhs < rhs,
+            CmpOp::Le => lhs <= rhs,
+            CmpOp::Gt => lhs > rhs,
+            CmpOp::Ge => lhs >= rhs,
+            CmpOp::Eq => lhs == rhs,
+            CmpOp::Ne => lhs != rhs,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Predicate {
+    Lts,
+    Released,
+    Supported,
+    Unsupported,
+    Devel,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum TextField {
+    Series,
+    Codename,
+    Version,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum DateValue {
+    /// A date field of the release being evaluated
+    Field(String),
+    Expr(DateExpr),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Predicate(Predicate),
+    CompareDate(String, CmpOp, DateValue),
+    CompareText(TextField, CmpOp, String),
+}
+
+/// A parsed filter expression
+#[derive(Clone, Debug, PartialEq)]
+pub struct Filter {
+    expr: Expr,
+}
+
+fn tokenize(value: &str) -> Result<Vec<Token>, Error> {
+    let is_word_char = |c: char| c.is_alphanumeric() || "_-.:+".contains(c);
+    let mut tokens = vec![];
+    let mut chars = value.char_indices().peekable();
+    while let Some((idx, c)) = chars.next() {
+        let next = chars.peek().map(|(_, next)| *next);
+        let (token, width) = match (c, next) {
+            _ if c.is_whitespace() => continue,
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('<', Some('=')) => (Token::Op(CmpOp::Le), 2),
+            ('>', Some('=')) => (Token::Op(CmpOp::Ge), 2),
+            ('=', Some('=')) => (Token::Op(CmpOp::Eq), 2),
+            ('!', Some('=')) => (Token::Op(CmpOp::Ne), 2),
+            ('<', _) => (Token::Op(CmpOp::Lt), 1),
+            ('>', _) => (Token::Op(CmpOp::Gt), 1),
+            ('=', _) => (Token::Op(CmpOp::Eq), 1),
+            ('!', _) => (Token::Not, 1),
+            ('(', _) => (Token::LParen, 1),
+            (')', _) => (Token::RParen, 1),
+            ('"', _) => {
+                let mut quoted = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, c)) => quoted.push(c),
+                        None => bail!("unterminated string in filter starting at offset {}", idx),
+                    }
+                }
+                (Token::Quoted(quoted), 1)
+            }
+            _ if is_word_char(c) => {
+                let mut word = c.to_string();
+                while let Some((_, c)) = chars.next_if(|(_, c)| is_word_char(*c)) {
+                    word.push(c);
+                }
+                (Token::Word(word), 1)
+            }
+            _ => bail!("unexpected character `{}' in filter at offset {}", c, idx),
+        };
+        if width == 2 {
+            chars.next();
+        }
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// Returns the canonical name of a date field, if `name` is one
+fn date_field(name: &str) -> Option<String> {
+    let name = name.replace('_', "-");
+    match name.as_str() {
+        "created" | "release" => Some(name),
+        _ => name.parse::<Milestone>().ok().map(|_| name),
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, Error> {
+        match self.next() {
+            Some(Token::Not) => Ok(Expr::Not(Box::new(self.parse_unary()?))),
+            Some(Token::LParen) => {
+                let expr = self.parse_or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => bail!("expected `)' in filter"),
+                }
+            }
+            Some(Token::Word(word)) => match self.peek() {
+                Some(Token::Op(op)) => {
+                    let op = *op;
+                    self.next();
+                    self.parse_comparison(&word, op)
+                }
+                _ => Ok(Expr::Predicate(match word.as_str() {
+                    "lts" => Predicate::Lts,
+                    "released" => Predicate::Released,
+                    "supported" => Predicate::Supported,
+                    "unsupported" => Predicate::Unsupported,
+                    "devel" => Predicate::Devel,
+                    _ => bail!("unknown predicate `{}' in filter", word),
+                })),
+            },
+            Some(token) => bail!("unexpected {:?} in filter", token),
+            None => bail!("unexpected end of filter"),
+        }
+    }
+
+    fn parse_comparison(&mut self, field: &str, op: CmpOp) -> Result<Expr, Error> {
+        let value = match self.next() {
+            Some(Token::Word(value)) | Some(Token::Quoted(value)) => value,
+            _ => bail!("expected a value after `{}' in filter", field),
+        };
+        let text_field = match field {
+            "series" => Some(TextField::Series),
+            "codename" => Some(TextField::Codename),
+            "version" => Some(TextField::Version),
+            _ => None,
+        };
+        if let Some(text_field) = text_field {
+            if !matches!(op, CmpOp::Eq | CmpOp::Ne) {
+                bail!("`{}' can only be compared with == or !=", field);
+            }
+            return Ok(Expr::CompareText(text_field, op, value));
+        }
+        let field =
+            date_field(field).ok_or_else(|| format_err!("unknown field `{}' in filter", field))?;
+        let value = if let Some(other_field) = date_field(&value) {
+            DateValue::Field(other_field)
+        } else {
+            let anchor = match value.split_once('.') {
+                Some((series, milestone)) if date_field(milestone).is_some() => {
+                    format!("{}:{}", series, milestone.replace('_', "-"))
+                }
+                _ => value,
+            };
+            DateValue::Expr(anchor.parse()?)
+        };
+        Ok(Expr::CompareDate(field, op, value))
+    }
+}
+
+impl FromStr for Filter {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Error> {
+        let mut parser = Parser {
+            tokens: tokenize(value)?,
+            position: 0,
+        };
+        let expr = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            bail!("unexpected {:?} in filter", token);
+        }
+        Ok(Self { expr })
+    }
+}
+
+impl Filter {
+    /// Whether `distro_release` matches this filter at `date`
+    ///
+    /// `distro_info` is used to look up `SERIES.MILESTONE` anchors and the distro's support rules.
+    pub fn matches(
+        &self,
+        distro_release: &DistroRelease,
+        distro_info: &impl DistroInfo,
+        date: NaiveDate,
+    ) -> Result<bool, Error> {
+        evaluate(&self.expr, distro_release, distro_info, date)
+    }
+
+    /// Returns those of `distro_releases` which match this filter at `date`
+    pub fn apply<'a>(
+        &self,
+        distro_releases: Vec<&'a DistroRelease>,
+        distro_info: &impl DistroInfo,
+        date: NaiveDate,
+    ) -> Result<Vec<&'a DistroRelease>, Error> {
+        let mut matching = vec![];
+        for distro_release in distro_releases {
+            if self.matches(distro_release, distro_info, date)? {
+                matching.push(distro_release);
+            }
+        }
+        Ok(matching)
+    }
+}
+
+fn evaluate(
+    expr: &Expr,
+    distro_release: &DistroRelease,
+    distro_info: &impl DistroInfo,
+    date: NaiveDate,
+) -> Result<bool, Error> {
+    let supported = || match distro_info.distro() {
+        Distro::Ubuntu => distro_release.ubuntu_supported_at(date),
+        Distro::Debian => distro_release.supported_at(date, &Milestone::Eol),
+    };
+    Ok(match expr {
+        Expr::And(lhs, rhs) => {
+            evaluate(lhs, distro_release, distro_info, date)?
+                && evaluate(rhs, distro_release, distro_info, date)?
+        }
+        Expr::Or(lhs, rhs) => {
+            evaluate(lhs, distro_release, distro_info, date)?
+                || evaluate(rhs, distro_release, distro_info, date)?
+        }
+        Expr::Not(expr) => !evaluate(expr, distro_release, distro_info, date)?,
+        Expr::Predicate(predicate) => match predicate {
+            Predicate::Lts => distro_info.is_lts_at(distro_release, date),
+            Predicate::Released => distro_release.released_at(date),
+            Predicate::Supported => supported(),
+            Predicate::Unsupported => distro_release.released_at(date) && !supported(),
+            Predicate::Devel => {
+                distro_release.created_at(date) && !distro_release.released_at(date)
+            }
+        },
+        Expr::CompareDate(field, op, value) => {
+            let lhs = named_milestone_date(distro_release, field)?;
+            let rhs = match value {
+                DateValue::Field(other_field) => named_milestone_date(distro_release, other_field)?,
+                DateValue::Expr(date_expr) => {
+                    Some(date_expr.resolve(distro_info, &FixedClock::new(date))?)
+                }
+            };
+            match (lhs, rhs) {
+                (Some(lhs), Some(rhs)) => op.compare(lhs, rhs),
+                _ => false,
+            }
+        }
+        Expr::CompareText(field, op, value) => {
+            let lhs = match field {
+                TextField::Series => Some(distro_release.series()),
+                TextField::Codename => Some(distro_release.codename()),
+                TextField::Version => distro_release.version().as_ref(),
+            };
+            match lhs {
+                Some(lhs) => op.compare(lhs.as_str(), value.as_str()),
+                None => false,
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Filter;
+    use crate::tests::naive_date;
+    use crate::{DebianDistroInfo, DistroInfo, UbuntuDistroInfo};
+
+    fn ubuntu_filter(expr: &str) -> Vec<String> {
+        let ubuntu_distro_info = UbuntuDistroInfo::new().unwrap();
+        let filter: Filter = expr.parse().unwrap();
+        filter
+            .apply(
+                ubuntu_distro_info.iter().collect(),
+                &ubuntu_distro_info,
+                naive_date(2024, 1, 1),
+            )
+            .unwrap()
+            .iter()
+            .map(|distro_release| distro_release.series().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn filter_lts_esm_before_noble() {
+        assert_eq!(
+            vec!["bionic", "focal", "jammy"],
+            ubuntu_filter("lts && eol_esm > 2026-12-31 && release < noble.release")
+        );
+    }
+
+    #[test]
+    fn filter_precedence_and_grouping() {
+        assert_eq!(
+            vec!["warty", "noble"],
+            ubuntu_filter("series == warty || lts && devel")
+        );
+        assert_eq!(
+            vec!["noble"],
+            ubuntu_filter("(series == warty || lts) && devel")
+        );
+        assert_eq!(
+            vec!["jammy"],
+            ubuntu_filter("codename == \"Jammy Jellyfish\"")
+        );
+    }
+
+    #[test]
+    fn filter_predicates() {
+        assert_eq!(vec!["noble"], ubuntu_filter("devel"));
+        assert_eq!(vec!["lunar", "mantic"], ubuntu_filter("supported && !lts"));
+        assert_eq!(
+            vec!["lunar", "mantic"],
+            ubuntu_filter("!lts && eol >= today && eol < +1y")
+        );
+        assert_eq!(
+            vec!["warty"],
+            ubuntu_filter("unsupported && version = 4.10")
+        );
+    }
+
+    #[test]
+    fn filter_debian_lts() {
+        let debian_distro_info = DebianDistroInfo::new().unwrap();
+        let filter: Filter = "lts".parse().unwrap();
+        let matching: Vec<_> = filter
+            .apply(
+                debian_distro_info.iter().collect(),
+                &debian_distro_info,
+                naive_date(2024, 1, 1),
+            )
+            .unwrap()
+            .iter()
+            .map(|distro_release| distro_release.series().to_string())
+            .collect();
+        assert_eq!(vec!["buster"], matching);
+    }
+
+    #[test]
+    fn filter_field_comparison() {
+        let debian_distro_info = DebianDistroInfo::new().unwrap();
+        let filter: Filter = "eol-lts > eol && eol_elts <= 2028-06-30".parse().unwrap();
+        let matching: Vec<_> = filter
+            .apply(
+                debian_distro_info.iter().collect(),
+                &debian_distro_info,
+                naive_date(2024, 1, 1),
+            )
+            .unwrap()
+            .iter()
+            .map(|distro_release| distro_release.series().to_string())
+            .collect();
+        assert_eq!(vec!["wheezy", "jessie", "stretch"], matching);
+    }
+
+    #[test]
+    fn filter_invalid() {
+        for expr in [
+            "",
+            "lts &&",
+            "(lts",
+            "bedtime",
+            "series < jammy",
+            "eol > whenever",
+            "lts supported",
+            "codename == \"Jammy",
+            "eol > 2024-01-01 $",
+        ] {
+            assert!(expr.parse::<Filter>().is_err(), "{}", expr);
+        }
+    }
+
+    #[test]
+    fn filter_unknown_anchor() {
+        let ubuntu_distro_info = UbuntuDistroInfo::new().unwrap();
+        let filter: Filter = "release < foobar.release".parse().unwrap();
+        let warty = ubuntu_distro_info.iter().next().unwrap();
+        assert!(filter
+            .matches(warty, &ubuntu_distro_info, naive_date(2024, 1, 1))
+            .is_err());
+    }
+}
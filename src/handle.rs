@@ -0,0 +1,193 @@
+//! Shared, automatically-reloading access to distro data for long-running processes.
+//!
+//! A [`DistroInfoHandle`](struct.DistroInfoHandle.html) loads its CSV file on first use and hands
+//! out `Arc` snapshots.  Each call to `get()` checks the file's metadata, and reloads it if the
+//! modification time, size or inode has changed, so that upgrades of distro-info-data take effect
+//! without restarting.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+use anyhow::Error;
+
+use crate::{DebianDistroInfo, DistroInfo, UbuntuDistroInfo};
+
+/// The metadata used to detect that a file has changed
+#[derive(Clone, Debug, PartialEq)]
+struct Fingerprint {
+    modified: Option<SystemTime>,
+    len: u64,
+    inode: Option<u64>,
+}
+
+impl Fingerprint {
+    fn of(path: &Path) -> Result<Self, Error> {
+        let metadata = fs::metadata(path)?;
+        #[cfg(unix)]
+        let inode = {
+            use std::os::unix::fs::MetadataExt;
+            Some(metadata.ino())
+        };
+        #[cfg(not(unix))]
+        let inode = None;
+        Ok(Self {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+            inode,
+        })
+    }
+}
+
+struct Loaded<T> {
+    fingerprint: Fingerprint,
+    distro_info: Arc<T>,
+}
+
+/// A thread-safe handle to the data in a CSV file, reloaded whenever the file changes
+pub struct DistroInfoHandle<T: DistroInfo> {
+    path: PathBuf,
+    loaded: Mutex<Option<Loaded<T>>>,
+}
+
+impl<T: DistroInfo> DistroInfoHandle<T> {
+    /// A handle to this distro's CSV file (as used by `DistroInfo::new()`)
+    ///
+    /// Nothing is read until the first call to `get()`.
+    pub fn new() -> Self {
+        Self::with_path(T::csv_path())
+    }
+
+    /// A handle to the CSV file at `path`
+    ///
+    /// Nothing is read until the first call to `get()`.
+    pub fn with_path<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            path: path.into(),
+            loaded: Mutex::new(None),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns a snapshot of the current data, (re)loading the file if it has changed
+    ///
+    /// If the file has changed but can't be reloaded (e.g. it is part-way through being
+    /// replaced), the previous snapshot is returned and reloading is retried on the next call.
+    /// An error is only returned if the file has never been loaded successfully.
+    pub fn get(&self) -> Result<Arc<T>, Error> {
+        let mut loaded = self.loaded.lock().unwrap();
+        let reloaded = Fingerprint::of(&self.path).and_then(|fingerprint| {
+            match loaded.as_ref() {
+                Some(current) if current.fingerprint == fingerprint => {
+                    return Ok(current.distro_info.clone())
+                }
+                _ => (),
+            }
+            let distro_info = Arc::new(T::from_csv_path(&self.path)?);
+            *loaded = Some(Loaded {
+                fingerprint,
+                distro_info: distro_info.clone(),
+            });
+            Ok(distro_info)
+        });
+        match (reloaded, loaded.as_ref()) {
+            (Ok(distro_info), _) => Ok(distro_info),
+            (Err(_), Some(current)) => Ok(current.distro_info.clone()),
+            (Err(error), None) => Err(error),
+        }
+    }
+}
+
+impl<T: DistroInfo> Default for DistroInfoHandle<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The process-wide handle to the Ubuntu data
+pub fn ubuntu() -> &'static DistroInfoHandle<UbuntuDistroInfo> {
+    static HANDLE: OnceLock<DistroInfoHandle<UbuntuDistroInfo>> = OnceLock::new();
+    HANDLE.get_or_init(DistroInfoHandle::new)
+}
+
+/// The process-wide handle to the Debian data
+pub fn debian() -> &'static DistroInfoHandle<DebianDistroInfo> {
+    static HANDLE: OnceLock<DistroInfoHandle<DebianDistroInfo>> = OnceLock::new();
+    HANDLE.get_or_init(DistroInfoHandle::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::sync::Arc;
+    use std::thread;
+
+    use super::DistroInfoHandle;
+    use crate::{DistroInfo, UbuntuDistroInfo};
+
+    const CSV: &str = "version,codename,series,created,release,eol
+4.10,Warty Warthog,warty,2004-03-05,2004-10-20,2006-04-30
+";
+    const HOARY: &str = "5.04,Hoary Hedgehog,hoary,2004-10-20,2005-04-08,2006-10-31
+";
+
+    fn series(distro_info: &UbuntuDistroInfo) -> Vec<String> {
+        distro_info
+            .iter()
+            .map(|distro_release| distro_release.series().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn handle_loads_lazily_and_reloads() {
+        let dir = std::env::temp_dir().join(format!("distro-info-handle-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ubuntu.csv");
+        let handle: DistroInfoHandle<UbuntuDistroInfo> = DistroInfoHandle::with_path(&path);
+        // Nothing has been loaded, so a missing file is an error
+        assert!(handle.get().is_err());
+
+        fs::write(&path, CSV).unwrap();
+        let first = handle.get().unwrap();
+        assert_eq!(vec!["warty"], series(&first));
+        // Unchanged files are not reloaded
+        assert!(Arc::ptr_eq(&first, &handle.get().unwrap()));
+
+        // Replace the file, as a package upgrade would
+        let new_path = dir.join("ubuntu.csv.new");
+        fs::write(&new_path, format!("{}{}", CSV, HOARY)).unwrap();
+        fs::rename(&new_path, &path).unwrap();
+        let second = handle.get().unwrap();
+        assert_eq!(vec!["warty", "hoary"], series(&second));
+        // Existing snapshots are unaffected
+        assert_eq!(vec!["warty"], series(&first));
+
+        // A broken file keeps the previous snapshot
+        fs::write(
+            &path,
+            "version,codename,series,created\n1,One,one,not-a-date\n",
+        )
+        .unwrap();
+        assert!(Arc::ptr_eq(&second, &handle.get().unwrap()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn handle_is_shareable_between_threads() {
+        let handle: Arc<DistroInfoHandle<UbuntuDistroInfo>> = Arc::new(DistroInfoHandle::new());
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let handle = handle.clone();
+                thread::spawn(move || handle.get().unwrap().iter().count())
+            })
+            .collect();
+        let expected = UbuntuDistroInfo::new().unwrap().iter().count();
+        for thread in threads {
+            assert_eq!(expected, thread.join().unwrap());
+        }
+    }
+}
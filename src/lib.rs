@@ -8,16 +8,38 @@ extern crate csv;
 #[macro_use]
 extern crate anyhow;
 
+pub mod clock;
+pub mod compat;
+pub mod date_expr;
+pub mod debian_base;
+pub mod detail;
 mod distro_release;
-
+pub mod filter;
+pub mod handle;
+pub mod overlap;
+pub mod overlay;
+pub mod package_version;
+pub mod resolve;
+pub mod stats;
+pub mod suggest;
+pub mod timeline;
+pub mod upgrade;
+
+use std::collections::BTreeMap;
 use std::env;
+use std::str::FromStr;
 
 use anyhow::Error;
 use chrono::naive::NaiveDate;
 use csv::ReaderBuilder;
 
-pub use crate::distro_release::DistroRelease;
+pub use crate::distro_release::{DistroRelease, SupportSegment};
+use crate::suggest::UnknownRelease;
+
+pub const OUTDATED_MSG: &str = "Distribution data outdated.
+Please check for an update for distro-info-data. See /usr/share/doc/distro-info-data/README.Debian for details.";
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Distro {
     Debian,
     Ubuntu,
@@ -36,13 +58,199 @@ fn parse_date(field: String) -> Result<NaiveDate, Error> {
     Ok(NaiveDate::parse_from_str(field.as_str(), "%Y-%m-%d")?)
 }
 
-#[derive(PartialEq)]
+/// The columns of distro-info-data records which `DistroRelease` has dedicated fields for
+pub const KNOWN_COLUMNS: [&str; 10] = [
+    "version",
+    "codename",
+    "series",
+    "created",
+    "release",
+    "eol",
+    "eol-lts",
+    "eol-elts",
+    "eol-esm",
+    "eol-server",
+];
+
+/// Create a `DistroRelease` from the (column, value) pairs of a distro-info-data record
+///
+/// Non-empty values in columns other than `KNOWN_COLUMNS` are kept as extra fields.
+pub(crate) fn release_from_fields(fields: &[(&str, &str)]) -> Result<DistroRelease, Error> {
+    let getfield = |n: &str| -> Option<String> {
+        fields
+            .iter()
+            .find(|(column, _)| *column == n)
+            .map(|(_, value)| value.to_string())
+    };
+    let parse_required_str = |field: Option<String>| -> Result<String, Error> {
+        field.ok_or(format_err!("failed to read required option"))
+    };
+    let mut extra_fields = BTreeMap::new();
+    for (column, value) in fields {
+        if !KNOWN_COLUMNS.contains(column) && !value.is_empty() {
+            extra_fields
+                .entry(column.to_string())
+                .or_insert_with(|| value.to_string());
+        }
+    }
+    Ok(DistroRelease::new(
+        parse_required_str(getfield("version"))?,
+        parse_required_str(getfield("codename"))?,
+        parse_required_str(getfield("series"))?,
+        getfield("created").map(parse_date).transpose()?,
+        getfield("release").map(parse_date).transpose()?,
+        getfield("eol").map(parse_date).transpose()?,
+        getfield("eol-lts").map(parse_date).transpose()?,
+        getfield("eol-elts").map(parse_date).transpose()?,
+        getfield("eol-esm").map(parse_date).transpose()?,
+        getfield("eol-server").map(parse_date).transpose()?,
+    )
+    .with_extra_fields(extra_fields))
+}
+
+/// A record's starting line, and the release parsed from it
+pub type ParsedRecord = (u64, Result<DistroRelease, Error>);
+
+/// Returns the alias (oldstable, stable, testing, unstable) of `series` at `date`, or `series`
+/// itself if it has none
+pub fn alias_for<'a>(distro_info: &impl DistroInfo, series: &'a str, date: NaiveDate) -> &'a str {
+    let is_match = |maybe_dr: Option<&DistroRelease>| -> bool {
+        maybe_dr.map(|dr| dr.series() == series).unwrap_or(false)
+    };
+    if is_match(distro_info.oldstable(date)) {
+        "oldstable"
+    } else if is_match(distro_info.latest(date)) {
+        "stable"
+    } else if is_match(distro_info.debian_testing(date)) {
+        "testing"
+    } else if is_match(distro_info.debian_devel(date).last().copied()) {
+        "unstable"
+    } else {
+        series
+    }
+}
+
+/// Parse each record read from `rdr`, paired with the line of the file it starts on
+///
+/// Unlike `DistroInfo::from_csv_reader`, a record which fails to parse doesn't prevent the others
+/// from being parsed; only failing to read the header row is an error overall.
+pub fn parse_records<T: std::io::Read>(
+    mut rdr: csv::Reader<T>,
+) -> Result<Vec<ParsedRecord>, Error> {
+    let columns = rdr.headers()?.clone();
+    let mut records = vec![];
+    let mut line = 1;
+    for record in rdr.records() {
+        let position = match &record {
+            Ok(record) => record.position(),
+            Err(error) => error.position(),
+        };
+        line = position.map_or(line + 1, |position| position.line());
+        let distro_release = record.map_err(Error::from).and_then(|record| {
+            let fields: Vec<_> = columns.iter().zip(record.iter()).collect();
+            release_from_fields(&fields)
+        });
+        records.push((line, distro_release));
+    }
+    Ok(records)
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum Milestone {
     Eol,
     EolELTS,
     EolESM,
     EolLTS,
     EolServer,
+    /// A date-valued extra column (see `DistroRelease::extra_field`)
+    Custom(String),
+}
+
+impl FromStr for Milestone {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Error> {
+        match value {
+            "eol" => Ok(Milestone::Eol),
+            "eol-elts" => Ok(Milestone::EolELTS),
+            "eol-esm" => Ok(Milestone::EolESM),
+            "eol-lts" => Ok(Milestone::EolLTS),
+            "eol-server" => Ok(Milestone::EolServer),
+            _ => bail!("unknown milestone `{}'", value),
+        }
+    }
+}
+
+/// A selection of releases, with the semantics of the corresponding `ubuntu-distro-info` and
+/// `debian-distro-info` flags
+#[derive(Clone, Debug, PartialEq)]
+pub enum Selector {
+    /// All known releases (`--all`)
+    All,
+    /// Supported releases (`--supported`); Ubuntu's rules consider server EOL dates
+    Supported,
+    /// Releases still covered by ESM, e.g. with Ubuntu Pro (`--supported-esm`)
+    SupportedEsm,
+    /// Released but unsupported releases (`--unsupported`)
+    Unsupported,
+    /// The development release(s) (`--devel`)
+    Devel,
+    /// The Debian testing release (`--testing`)
+    Testing,
+    /// The development release if there is one, otherwise the latest stable (`--latest`)
+    Latest,
+    /// The latest Ubuntu LTS, or the Debian releases in LTS support (`--lts`)
+    Lts,
+    /// The Debian releases in Extended LTS support (`--elts`)
+    Elts,
+    /// The stable release prior to the latest (`--oldstable`)
+    Oldstable,
+    /// The latest stable release (`--stable`)
+    Stable,
+    /// The release(s) with this series (`--series`)
+    Series(String),
+}
+
+impl FromStr for Selector {
+    type Err = Error;
+
+    /// Parse a selector from its flag name (without the leading `--`); `--series` is not accepted
+    fn from_str(value: &str) -> Result<Self, Error> {
+        match value {
+            "all" => Ok(Selector::All),
+            "supported" => Ok(Selector::Supported),
+            "supported-esm" => Ok(Selector::SupportedEsm),
+            "unsupported" => Ok(Selector::Unsupported),
+            "devel" => Ok(Selector::Devel),
+            "testing" => Ok(Selector::Testing),
+            "latest" => Ok(Selector::Latest),
+            "lts" => Ok(Selector::Lts),
+            "elts" => Ok(Selector::Elts),
+            "oldstable" => Ok(Selector::Oldstable),
+            "stable" => Ok(Selector::Stable),
+            _ => bail!("unknown selector `{}'", value),
+        }
+    }
+}
+
+/// A stage of a release's support lifecycle
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    Standard,
+    LTS,
+    ELTS,
+    ESM,
+}
+
+impl Phase {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Standard => "standard",
+            Phase::LTS => "LTS",
+            Phase::ELTS => "ELTS",
+            Phase::ESM => "ESM",
+        }
+    }
 }
 
 pub trait DistroInfo: Sized {
@@ -58,49 +266,29 @@ pub trait DistroInfo: Sized {
     ///
     /// (These records must be in the format used in debian.csv/ubuntu.csv as provided by the
     /// distro-info-data package in Debian/Ubuntu.)
-    fn from_csv_reader<T: std::io::Read>(mut rdr: csv::Reader<T>) -> Result<Self, Error> {
-        let columns = rdr.headers()?.clone();
-        let parse_required_str = |field: Option<String>| -> Result<String, Error> {
-            field.ok_or(format_err!("failed to read required option"))
-        };
-        let getfield = |r: &csv::StringRecord, n: &str| -> Option<String> {
-            columns
-                .iter()
-                .position(|header| header == n)
-                .and_then(|i| r.get(i))
-                .map(|s| s.to_string())
-        };
-        let mut releases = vec![];
-        for record in rdr.records() {
-            let record = record?;
-            releases.push(DistroRelease::new(
-                parse_required_str(getfield(&record, "version"))?,
-                parse_required_str(getfield(&record, "codename"))?,
-                parse_required_str(getfield(&record, "series"))?,
-                getfield(&record, "created").map(parse_date).transpose()?,
-                getfield(&record, "release").map(parse_date).transpose()?,
-                getfield(&record, "eol").map(parse_date).transpose()?,
-                getfield(&record, "eol-lts").map(parse_date).transpose()?,
-                getfield(&record, "eol-elts").map(parse_date).transpose()?,
-                getfield(&record, "eol-esm").map(parse_date).transpose()?,
-                getfield(&record, "eol-server")
-                    .map(parse_date)
-                    .transpose()?,
-            ))
-        }
+    fn from_csv_reader<T: std::io::Read>(rdr: csv::Reader<T>) -> Result<Self, Error> {
+        let releases = parse_records(rdr)?
+            .into_iter()
+            .map(|(_, distro_release)| distro_release)
+            .collect::<Result<Vec<_>, _>>()?;
         Ok(Self::from_vec(releases))
     }
 
-    /// Open this distro's CSV file and parse the release data contained therein
-    fn new() -> Result<Self, Error> {
+    /// Open the CSV file at `path` and parse the release data contained therein
+    fn from_csv_path<P: AsRef<std::path::Path>>(path: P) -> Result<Self, Error> {
         Self::from_csv_reader(
             ReaderBuilder::new()
                 .flexible(true)
                 .has_headers(true)
-                .from_path(Self::csv_path())?,
+                .from_path(path)?,
         )
     }
 
+    /// Open this distro's CSV file and parse the release data contained therein
+    fn new() -> Result<Self, Error> {
+        Self::from_csv_path(Self::csv_path())
+    }
+
     /// Returns a vector of `DistroRelease`s for releases that had been created at the given date
     fn all_at(&self, date: NaiveDate) -> Vec<&DistroRelease> {
         self.releases()
@@ -185,6 +373,33 @@ pub trait DistroInfo: Sized {
             .unwrap_or_else(std::vec::Vec::new)
     }
 
+    /// Whether `distro_release` counts as LTS at the given date, as `--lts` means it: an Ubuntu LTS
+    /// release, or a Debian release past its EOL but still covered by LTS
+    fn is_lts_at(&self, distro_release: &DistroRelease, date: NaiveDate) -> bool {
+        match self.distro() {
+            Distro::Ubuntu => distro_release.ubuntu_is_lts(),
+            Distro::Debian => {
+                !distro_release.supported_at(date, &Milestone::Eol)
+                    && distro_release.supported_at(date, &Milestone::EolLTS)
+            }
+        }
+    }
+
+    /// Returns a `DistroRelease` for the release that was Debian's testing at the given date
+    ///
+    /// This is the newest release created by then which has a version and either has no release
+    /// date yet or hadn't been released, as in upstream's `debian-distro-info --testing`.
+    fn debian_testing(&self, date: NaiveDate) -> Option<&DistroRelease> {
+        self.all_at(date)
+            .into_iter()
+            .rfind(|distro_release| match distro_release.release() {
+                None => distro_release.version().is_some(),
+                Some(release) => {
+                    date < *release && distro_release.eol().is_none_or(|eol| date <= eol)
+                }
+            })
+    }
+
     /// Returns a `DistroRelease` for the latest supported, non-EOL release at the given date
     fn latest(&self, date: NaiveDate) -> Option<&DistroRelease> {
         self.ubuntu_supported(date)
@@ -206,7 +421,81 @@ pub trait DistroInfo: Sized {
         candidate_idx.and_then(|idx| candidates.get(idx).copied())
     }
 
-    fn iter(&self) -> ::std::slice::Iter<DistroRelease> {
+    /// Returns the releases chosen by `selector` at the given date, exactly as the equivalent
+    /// `ubuntu-distro-info`/`debian-distro-info` flag would
+    fn select(&self, selector: &Selector, date: NaiveDate) -> Result<Vec<&DistroRelease>, Error> {
+        Ok(match selector {
+            Selector::All => self.iter().collect(),
+            Selector::Supported => match self.distro() {
+                Distro::Ubuntu => self.ubuntu_supported(date),
+                Distro::Debian => self.supported(date, Milestone::Eol),
+            },
+            Selector::SupportedEsm => self.supported(date, Milestone::EolESM),
+            Selector::Unsupported => match self.distro() {
+                Distro::Ubuntu => self.ubuntu_unsupported(date),
+                Distro::Debian => self.unsupported(date, Milestone::Eol),
+            },
+            Selector::Devel => match self.distro() {
+                Distro::Ubuntu => self.ubuntu_devel(date),
+                Distro::Debian => self.debian_devel(date),
+            },
+            // d-d-i --testing selection matches u-d-i --devel
+            Selector::Testing => self.debian_testing(date).into_iter().collect(),
+            Selector::Latest => {
+                let devel_result = self.ubuntu_devel(date);
+                if !devel_result.is_empty() {
+                    vec![*devel_result.last().unwrap()]
+                } else {
+                    self.latest(date)
+                        .map(|distro_release| vec![distro_release])
+                        .unwrap_or_default()
+                }
+            }
+            Selector::Lts => {
+                let lts_releases: Vec<_> = self
+                    .all_at(date)
+                    .into_iter()
+                    .filter(|distro_release| self.is_lts_at(distro_release, date))
+                    .collect();
+                match self.distro() {
+                    Distro::Ubuntu => match lts_releases.last() {
+                        Some(release) => vec![*release],
+                        None => bail!(OUTDATED_MSG),
+                    },
+                    Distro::Debian => lts_releases,
+                }
+            }
+            Selector::Elts => self
+                .released(date)
+                .into_iter()
+                .filter(|distro_release| !distro_release.supported_at(date, &Milestone::EolLTS))
+                .filter(|distro_release| distro_release.supported_at(date, &Milestone::EolELTS))
+                .collect(),
+            Selector::Oldstable => self
+                .oldstable(date)
+                .map(|distro_release| vec![distro_release])
+                .unwrap_or_default(),
+            Selector::Stable => self
+                .latest(date)
+                .map(|distro_release| vec![distro_release])
+                .unwrap_or_default(),
+            Selector::Series(needle_series) => {
+                if !needle_series.chars().all(|c| c.is_lowercase()) {
+                    bail!("invalid distribution series `{}'", needle_series);
+                };
+                let candidates: Vec<&DistroRelease> = self
+                    .iter()
+                    .filter(|distro_release| distro_release.series() == needle_series)
+                    .collect();
+                if candidates.is_empty() {
+                    bail!(UnknownRelease::new(self, "series", needle_series));
+                };
+                candidates
+            }
+        })
+    }
+
+    fn iter(&self) -> ::std::slice::Iter<'_, DistroRelease> {
         self.releases().iter()
     }
 }
@@ -225,7 +514,12 @@ impl DistroInfo for UbuntuDistroInfo {
     }
     /// Initialise an UbuntuDistroInfo struct from a vector of DistroReleases
     fn from_vec(releases: Vec<DistroRelease>) -> Self {
-        Self { releases }
+        Self {
+            releases: releases
+                .into_iter()
+                .map(|distro_release| distro_release.with_distro(Distro::Ubuntu))
+                .collect(),
+        }
     }
 }
 
@@ -244,7 +538,7 @@ pub struct DebianDistroInfo {
 
 impl DebianDistroInfo {
     pub fn stable(&self, date: NaiveDate) -> Option<&DistroRelease> {
-        self.released(date).into_iter().rev().next()
+        self.released(date).into_iter().next_back()
     }
 
     pub fn oldstable(&self, date: NaiveDate) -> Option<&DistroRelease> {
@@ -252,16 +546,7 @@ impl DebianDistroInfo {
     }
 
     pub fn testing(&self, date: NaiveDate) -> Option<&DistroRelease> {
-        self.iter().find(|release| {
-            if let Some(created) = release.created() {
-                date > *created
-                    && (release.release().is_none() || date < release.release().unwrap())
-                    && release.series() != "sid"
-                    && release.series() != "experimental"
-            } else {
-                false
-            }
-        })
+        self.debian_testing(date)
     }
 
     pub fn unstable(&self) -> &DistroRelease {
@@ -289,7 +574,12 @@ impl DistroInfo for DebianDistroInfo {
     }
     /// Initialise an DebianDistroInfo struct from a vector of DistroReleases
     fn from_vec(releases: Vec<DistroRelease>) -> Self {
-        Self { releases }
+        Self {
+            releases: releases
+                .into_iter()
+                .map(|distro_release| distro_release.with_distro(Distro::Debian))
+                .collect(),
+        }
     }
 }
 
@@ -305,7 +595,10 @@ impl IntoIterator for DebianDistroInfo {
 #[cfg(test)]
 mod tests {
     use chrono::naive::NaiveDate;
-    use {super::DebianDistroInfo, super::DistroInfo, super::UbuntuDistroInfo};
+    use {
+        super::alias_for, super::resolve, super::DebianDistroInfo, super::DistroInfo,
+        super::Milestone, super::Selector, super::UbuntuDistroInfo,
+    };
 
     pub fn naive_date(year: i32, month: u32, day: u32) -> NaiveDate {
         NaiveDate::from_ymd_opt(year, month, day).unwrap()
@@ -345,6 +638,43 @@ mod tests {
         assert_eq!(&None, distro_release.eol_server());
     }
 
+    #[test]
+    fn extra_columns_as_custom_milestones() {
+        let csv = "version,codename,series,created,release,eol,eol-contract,owner
+20.04 LTS,Focal Fossa,focal,2019-10-17,2020-04-23,2025-05-29,2032-04-30,platform
+22.04 LTS,Jammy Jellyfish,jammy,2021-10-21,2022-04-21,2027-06-01,,
+";
+        let ubuntu_distro_info = UbuntuDistroInfo::from_csv_reader(
+            csv::ReaderBuilder::new().from_reader(csv.as_bytes()),
+        )
+        .unwrap();
+        let focal = ubuntu_distro_info.iter().next().unwrap();
+        assert_eq!(Some("2032-04-30"), focal.extra_field("eol-contract"));
+        assert_eq!(Some("platform"), focal.extra_field("owner"));
+        assert_eq!(None, focal.extra_field("eol"));
+        let contract = Milestone::Custom("eol-contract".to_string());
+        let date = naive_date(2030, 1, 1);
+        let series = |distro_releases: Vec<&super::DistroRelease>| -> Vec<String> {
+            distro_releases
+                .iter()
+                .map(|distro_release| distro_release.series().to_string())
+                .collect()
+        };
+        assert_eq!(
+            vec!["focal"],
+            series(ubuntu_distro_info.supported(date, contract.clone()))
+        );
+        // Releases without the column are unsupported per that milestone
+        assert_eq!(
+            vec!["jammy"],
+            series(ubuntu_distro_info.unsupported(date, contract))
+        );
+        // Non-date columns never mark a milestone
+        assert!(ubuntu_distro_info
+            .supported(date, Milestone::Custom("owner".to_string()))
+            .is_empty());
+    }
+
     #[test]
     fn ubuntu_distro_info_eol_server() {
         let ubuntu_distro_info = UbuntuDistroInfo::new().unwrap();
@@ -507,6 +837,25 @@ mod tests {
         let debian_distro_info = DebianDistroInfo::new().unwrap();
         let testing = debian_distro_info.testing(naive_date(2021, 7, 26)).unwrap();
         assert_eq!(testing.series(), "bullseye");
+        // Forky has no release date in the data; it is testing from trixie's release
+        for date in [naive_date(2025, 9, 1), naive_date(2026, 10, 18)] {
+            assert_eq!(
+                vec!["forky"],
+                debian_distro_info
+                    .select(&Selector::Testing, date)
+                    .unwrap()
+                    .iter()
+                    .map(|distro_release| distro_release.series().as_str())
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!("testing", alias_for(&debian_distro_info, "forky", date));
+            assert_eq!(
+                "forky",
+                resolve::resolve(&debian_distro_info, "testing", date)
+                    .unwrap()
+                    .series()
+            );
+        }
     }
 
     #[test]
@@ -522,4 +871,70 @@ mod tests {
         let experimental = debian_distro_info.experimental();
         assert_eq!(experimental.series(), "experimental");
     }
+
+    #[test]
+    fn selector_from_str() {
+        assert_eq!(Selector::Elts, "elts".parse().unwrap());
+        assert!("series".parse::<Selector>().is_err());
+    }
+
+    #[test]
+    fn ubuntu_select() {
+        let ubuntu_distro_info = UbuntuDistroInfo::new().unwrap();
+        let date = naive_date(2018, 4, 26);
+        let select = |selector: Selector| -> Vec<String> {
+            ubuntu_distro_info
+                .select(&selector, date)
+                .unwrap()
+                .iter()
+                .map(|distro_release| distro_release.series().to_string())
+                .collect()
+        };
+        assert_eq!(
+            vec!["trusty", "xenial", "artful", "bionic", "cosmic"],
+            select(Selector::Supported)
+        );
+        assert_eq!(vec!["cosmic"], select(Selector::Devel));
+        assert_eq!(vec!["cosmic"], select(Selector::Latest));
+        assert_eq!(vec!["bionic"], select(Selector::Lts));
+        assert_eq!(vec!["bionic"], select(Selector::Stable));
+        assert_eq!(
+            vec!["precise", "trusty", "xenial", "bionic"],
+            select(Selector::SupportedEsm)
+        );
+        assert_eq!(
+            vec!["xenial"],
+            select(Selector::Series("xenial".to_string()))
+        );
+        assert!(ubuntu_distro_info
+            .select(&Selector::Series("Xenial".to_string()), date)
+            .is_err());
+        assert!(ubuntu_distro_info
+            .select(&Selector::Series("foobar".to_string()), date)
+            .is_err());
+    }
+
+    #[test]
+    fn debian_select() {
+        let debian_distro_info = DebianDistroInfo::new().unwrap();
+        let date = naive_date(2023, 1, 1);
+        let select = |selector: Selector| -> Vec<String> {
+            debian_distro_info
+                .select(&selector, date)
+                .unwrap()
+                .iter()
+                .map(|distro_release| distro_release.series().to_string())
+                .collect()
+        };
+        assert_eq!(
+            vec!["bullseye", "bookworm", "sid", "experimental"],
+            select(Selector::Supported)
+        );
+        assert_eq!(vec!["bookworm"], select(Selector::Testing));
+        assert_eq!(vec!["sid"], select(Selector::Devel));
+        assert_eq!(vec!["buster"], select(Selector::Oldstable));
+        assert_eq!(vec!["bullseye"], select(Selector::Stable));
+        assert_eq!(vec!["buster"], select(Selector::Lts));
+        assert_eq!(vec!["jessie", "stretch"], select(Selector::Elts));
+    }
 }
@@ -0,0 +1,157 @@
+//! Compute the windows during which two releases are both supported.
+//!
+//! Each release is considered supported per a milestone from its release date until that
+//! milestone's date.  As with `DistroRelease::supported_at`, a release with no `eol` date is
+//! supported indefinitely, but a release without any other milestone has no such phase of support.
+use chrono::NaiveDate;
+
+use crate::{DistroRelease, Milestone};
+
+/// A period during which two releases are both supported
+#[derive(Clone, Debug, PartialEq)]
+pub struct Overlap<'a> {
+    pub older: &'a DistroRelease,
+    pub newer: &'a DistroRelease,
+    pub start: NaiveDate,
+    /// The last day of the overlap; `None` if neither release has an end date yet
+    pub end: Option<NaiveDate>,
+}
+
+impl Overlap<'_> {
+    /// The length of the overlap in days (counting both its first and last days), if it has ended
+    pub fn days(&self) -> Option<i64> {
+        self.end
+            .map(|end| end.signed_duration_since(self.start).num_days() + 1)
+    }
+}
+
+/// Returns the period from `distro_release`'s release until `milestone`, if it has one
+fn support_window(
+    distro_release: &DistroRelease,
+    milestone: &Milestone,
+) -> Option<(NaiveDate, Option<NaiveDate>)> {
+    let release = (*distro_release.release())?;
+    match distro_release.milestone_date(milestone) {
+        Some(end) => Some((release, Some(end))),
+        None if milestone == &Milestone::Eol => Some((release, None)),
+        None => None,
+    }
+}
+
+/// Returns the period during which both `a` and `b` are supported per `milestone`, if there is one
+///
+/// The releases may be given in either order; the earlier-released one is `older` in the result.
+pub fn overlap<'a>(
+    a: &'a DistroRelease,
+    b: &'a DistroRelease,
+    milestone: &Milestone,
+) -> Option<Overlap<'a>> {
+    let (a_start, a_end) = support_window(a, milestone)?;
+    let (b_start, b_end) = support_window(b, milestone)?;
+    let (older, newer) = if (a_start, a.series()) <= (b_start, b.series()) {
+        (a, b)
+    } else {
+        (b, a)
+    };
+    let start = a_start.max(b_start);
+    let end = match (a_end, b_end) {
+        (Some(a_end), Some(b_end)) => Some(a_end.min(b_end)),
+        (end, None) | (None, end) => end,
+    };
+    if end.is_some_and(|end| end < start) {
+        return None;
+    }
+    Some(Overlap {
+        older,
+        newer,
+        start,
+        end,
+    })
+}
+
+/// Returns the overlaps between each release in `distro_releases` and the one following it
+///
+/// Successive releases which don't overlap are skipped.
+pub fn successive_overlaps<'a>(
+    distro_releases: &[&'a DistroRelease],
+    milestone: &Milestone,
+) -> Vec<Overlap<'a>> {
+    distro_releases
+        .windows(2)
+        .filter_map(|pair| overlap(pair[0], pair[1], milestone))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{overlap, successive_overlaps};
+    use crate::tests::naive_date;
+    use crate::{DebianDistroInfo, DistroInfo, DistroRelease, Milestone, UbuntuDistroInfo};
+
+    fn find<'a>(distro_info: &'a impl DistroInfo, series: &str) -> &'a DistroRelease {
+        distro_info
+            .iter()
+            .find(|distro_release| distro_release.series() == series)
+            .unwrap()
+    }
+
+    #[test]
+    fn overlap_between_pairs() {
+        let ubuntu_distro_info = UbuntuDistroInfo::new().unwrap();
+        let jammy = find(&ubuntu_distro_info, "jammy");
+        let noble = find(&ubuntu_distro_info, "noble");
+        let overlap_window = overlap(noble, jammy, &Milestone::Eol).unwrap();
+        assert_eq!("jammy", overlap_window.older.series());
+        assert_eq!("noble", overlap_window.newer.series());
+        assert_eq!(naive_date(2024, 4, 25), overlap_window.start);
+        assert_eq!(Some(naive_date(2027, 6, 1)), overlap_window.end);
+        assert_eq!(Some(1133), overlap_window.days());
+
+        // Releases whose support doesn't meet don't overlap
+        let xenial = find(&ubuntu_distro_info, "xenial");
+        assert_eq!(None, overlap(xenial, noble, &Milestone::Eol));
+        // ... and neither do releases lacking the milestone
+        let mantic = find(&ubuntu_distro_info, "mantic");
+        assert_eq!(None, overlap(mantic, noble, &Milestone::EolESM));
+        assert!(overlap(xenial, noble, &Milestone::EolESM).is_some());
+    }
+
+    #[test]
+    fn overlap_without_eol() {
+        let debian_distro_info = DebianDistroInfo::new().unwrap();
+        let trixie = find(&debian_distro_info, "trixie");
+        let bookworm = find(&debian_distro_info, "bookworm");
+        let overlap_window = overlap(bookworm, trixie, &Milestone::Eol).unwrap();
+        assert_eq!(*trixie.release(), Some(overlap_window.start));
+        assert_eq!(*bookworm.eol(), overlap_window.end);
+        // Unreleased releases have no support window
+        let forky = find(&debian_distro_info, "forky");
+        assert_eq!(None, overlap(trixie, forky, &Milestone::Eol));
+    }
+
+    #[test]
+    fn overlaps_of_successive_releases() {
+        let debian_distro_info = DebianDistroInfo::new().unwrap();
+        let distro_releases: Vec<_> = debian_distro_info
+            .iter()
+            .filter(|distro_release| {
+                ["buster", "bullseye", "bookworm"].contains(&distro_release.series().as_str())
+            })
+            .collect();
+        let overlaps = successive_overlaps(&distro_releases, &Milestone::EolLTS);
+        let pairs: Vec<_> = overlaps
+            .iter()
+            .map(|overlap| {
+                (
+                    overlap.older.series().as_str(),
+                    overlap.newer.series().as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![("buster", "bullseye"), ("bullseye", "bookworm")],
+            pairs
+        );
+        assert_eq!(*distro_releases[0].eol_lts(), overlaps[0].end);
+    }
+}
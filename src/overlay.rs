@@ -0,0 +1,456 @@
+//! Amend or extend distro-info-data with additional CSV or TOML files.
+//!
+//! Overlay files use the same columns as debian.csv/ubuntu.csv, but need only contain a `series`
+//! column and the columns they change.  A row whose series matches an existing release overrides
+//! that release's non-empty fields; a row with a new series is added as a new release (and so
+//! must provide every column a release requires), placed among the others by its release date
+//! (or its created date, if it has none).  Overlays are applied in order, so later files take
+//! precedence, and the file each field was last taken from is recorded.
+//!
+//! With the `toml` feature, overlay files whose name ends in `.toml` are read as TOML: each row is
+//! a `[[release]]` table whose keys are column names, with string, date or integer values.
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{Context, Error};
+use chrono::NaiveDate;
+use csv::ReaderBuilder;
+
+use crate::{release_from_fields, DistroInfo};
+
+/// A release's fields, in the order their columns were first seen
+struct Row {
+    fields: Vec<(String, String)>,
+}
+
+impl Row {
+    fn get(&self, name: &str) -> Option<String> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.clone())
+    }
+
+    fn set(&mut self, name: &str, value: &str) {
+        match self.fields.iter_mut().find(|(field, _)| field == name) {
+            Some((_, existing)) => *existing = value.to_string(),
+            None => self.fields.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    /// The date the row is ordered by: its release date, or its created date if it has none
+    fn sort_date(&self) -> Option<NaiveDate> {
+        ["release", "created"]
+            .iter()
+            .find_map(|column| NaiveDate::parse_from_str(&self.get(column)?, "%Y-%m-%d").ok())
+    }
+
+    /// Whether `new` belongs before this row
+    ///
+    /// Releases without a version (Debian's sid and experimental) stay at the end.
+    fn is_after(&self, new: &Row) -> bool {
+        if self.get("version").is_none_or(|version| version.is_empty()) {
+            return true;
+        }
+        match (self.sort_date(), new.sort_date()) {
+            (Some(date), Some(new_date)) => date > new_date,
+            _ => false,
+        }
+    }
+}
+
+/// The (column, value) pairs of each row of a source
+type Records = Vec<Vec<(String, String)>>;
+
+/// Read the rows of the CSV data in `rdr`, checking that each has a series
+fn csv_records<R: Read>(name: &str, mut rdr: csv::Reader<R>) -> Result<Records, Error> {
+    let columns = rdr
+        .headers()
+        .with_context(|| format!("{}: failed to read header", name))?
+        .clone();
+    if !columns.iter().any(|column| column == "series") {
+        bail!("{}: no `series' column", name);
+    }
+    let mut records = vec![];
+    for record in rdr.records() {
+        let record = record.with_context(|| format!("{}: invalid record", name))?;
+        let fields: Vec<(String, String)> = columns
+            .iter()
+            .zip(record.iter())
+            .map(|(column, value)| (column.to_string(), value.to_string()))
+            .collect();
+        if !fields
+            .iter()
+            .any(|(column, value)| column == "series" && !value.is_empty())
+        {
+            bail!(
+                "{}: line {} has no series",
+                name,
+                record.position().map_or(0, |position| position.line())
+            );
+        }
+        records.push(fields);
+    }
+    Ok(records)
+}
+
+/// Read the `[[release]]` tables of the TOML document `content`, checking that each has a series
+#[cfg(feature = "toml")]
+fn toml_records(name: &str, content: &str) -> Result<Records, Error> {
+    let document: toml::Table = content
+        .parse()
+        .with_context(|| format!("{}: invalid TOML", name))?;
+    let releases = match document.get("release") {
+        None => return Ok(vec![]),
+        Some(toml::Value::Array(releases)) => releases,
+        Some(_) => bail!("{}: `release' must be an array of tables", name),
+    };
+    let mut records = vec![];
+    for (index, release) in releases.iter().enumerate() {
+        let table = release
+            .as_table()
+            .ok_or_else(|| format_err!("{}: release {} is not a table", name, index + 1))?;
+        let mut fields = vec![];
+        for (column, value) in table {
+            let value = match value {
+                toml::Value::String(value) => value.clone(),
+                toml::Value::Datetime(value) => value.to_string(),
+                toml::Value::Integer(value) => value.to_string(),
+                _ => bail!(
+                    "{}: release {}: `{}' must be a string, date or integer",
+                    name,
+                    index + 1,
+                    column
+                ),
+            };
+            fields.push((column.clone(), value));
+        }
+        if !fields.iter().any(|(column, _)| column == "series") {
+            bail!("{}: release {} has no series", name, index + 1);
+        }
+        records.push(fields);
+    }
+    Ok(records)
+}
+
+/// Read the overlay file at `path`, as TOML if its name ends in `.toml` and CSV otherwise
+fn overlay_records(path: &Path) -> Result<Records, Error> {
+    let name = path.display().to_string();
+    if path
+        .extension()
+        .is_some_and(|extension| extension == "toml")
+    {
+        #[cfg(feature = "toml")]
+        {
+            let content = std::fs::read_to_string(path)
+                .map_err(|e| format_err!("failed to open {}: {}", name, e))?;
+            return toml_records(&name, &content);
+        }
+        #[cfg(not(feature = "toml"))]
+        bail!("{}: TOML overlays need the `toml' feature", name);
+    }
+    let rdr = ReaderBuilder::new()
+        .flexible(true)
+        .has_headers(true)
+        .from_path(path)
+        .map_err(|e| format_err!("failed to open {}: {}", name, e))?;
+    csv_records(&name, rdr)
+}
+
+/// Distro data merged from a base file and any number of overlays
+pub struct Overlaid<T: DistroInfo> {
+    distro_info: T,
+    sources: Vec<String>,
+    provenance: HashMap<(String, String), usize>,
+}
+
+impl<T: DistroInfo> Overlaid<T> {
+    /// Load the CSV file at `base` and apply the files at `overlays` to it, in order
+    pub fn load<B: AsRef<Path>, O: AsRef<Path>>(base: B, overlays: &[O]) -> Result<Self, Error> {
+        let base = base.as_ref();
+        let rdr = ReaderBuilder::new()
+            .flexible(true)
+            .has_headers(true)
+            .from_path(base)
+            .map_err(|e| format_err!("failed to open {}: {}", base.display(), e))?;
+        let mut sources = vec![(
+            base.display().to_string(),
+            csv_records(&base.display().to_string(), rdr)?,
+        )];
+        for path in overlays.iter().map(AsRef::as_ref) {
+            sources.push((path.display().to_string(), overlay_records(path)?));
+        }
+        Self::merge(sources)
+    }
+
+    /// Merge the given `(source name, reader)` pairs; the first is the base data
+    pub fn from_readers<R: Read>(sources: Vec<(String, csv::Reader<R>)>) -> Result<Self, Error> {
+        let sources = sources
+            .into_iter()
+            .map(|(name, rdr)| Ok((name.clone(), csv_records(&name, rdr)?)))
+            .collect::<Result<Vec<_>, Error>>()?;
+        Self::merge(sources)
+    }
+
+    /// Merge the rows of each `(source name, rows)` pair; the first is the base data
+    fn merge(sources: Vec<(String, Records)>) -> Result<Self, Error> {
+        let mut rows: Vec<Row> = vec![];
+        let mut names = vec![];
+        let mut provenance = HashMap::new();
+        for (index, (name, records)) in sources.into_iter().enumerate() {
+            for fields in records {
+                let series = fields
+                    .iter()
+                    .find(|(column, _)| column == "series")
+                    .map(|(_, value)| value.clone())
+                    .unwrap_or_default();
+                let existing = rows
+                    .iter()
+                    .position(|row| row.get("series").as_deref() == Some(series.as_str()));
+                let mut new_row = Row { fields: vec![] };
+                let row = match existing {
+                    Some(position) => &mut rows[position],
+                    None => &mut new_row,
+                };
+                for (column, value) in &fields {
+                    // The base data uses empty fields (e.g. Debian's unversioned sid), but in
+                    // an overlay they mean "leave this field alone"
+                    if value.is_empty() && index > 0 {
+                        continue;
+                    }
+                    row.set(column, value);
+                    provenance.insert((series.clone(), column.clone()), index);
+                }
+                if existing.is_none() {
+                    // The base data is already in order; only new releases from overlays need
+                    // placing among it
+                    let position = match index {
+                        0 => rows.len(),
+                        _ => rows
+                            .iter()
+                            .position(|row| row.is_after(&new_row))
+                            .unwrap_or(rows.len()),
+                    };
+                    rows.insert(position, new_row);
+                }
+            }
+            names.push(name);
+        }
+        let mut releases = vec![];
+        for row in rows {
+            let fields: Vec<_> = row
+                .fields
+                .iter()
+                .map(|(column, value)| (column.as_str(), value.as_str()))
+                .collect();
+            let release = release_from_fields(&fields).with_context(|| {
+                format!(
+                    "invalid release `{}'",
+                    row.get("series").unwrap_or_default()
+                )
+            })?;
+            releases.push(release);
+        }
+        Ok(Self {
+            distro_info: T::from_vec(releases),
+            sources: names,
+            provenance,
+        })
+    }
+
+    pub fn distro_info(&self) -> &T {
+        &self.distro_info
+    }
+
+    pub fn into_distro_info(self) -> T {
+        self.distro_info
+    }
+
+    /// The names of the merged sources, base first
+    pub fn sources(&self) -> &[String] {
+        &self.sources
+    }
+
+    /// The name of the source the given field of the release with the given series came from
+    pub fn source_of(&self, series: &str, field: &str) -> Option<&str> {
+        self.provenance
+            .get(&(series.to_string(), field.to_string()))
+            .map(|index| self.sources[*index].as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Overlaid;
+    use crate::tests::naive_date;
+    use crate::{DebianDistroInfo, DistroInfo, UbuntuDistroInfo};
+
+    const BASE: &str = "version,codename,series,created,release,eol
+20.04 LTS,Focal Fossa,focal,2019-10-17,2020-04-23,2025-05-29
+22.04 LTS,Jammy Jellyfish,jammy,2021-10-21,2022-04-21,2027-06-01
+";
+
+    fn reader(content: &'static str) -> csv::Reader<&'static [u8]> {
+        csv::ReaderBuilder::new()
+            .flexible(true)
+            .from_reader(content.as_bytes())
+    }
+
+    #[test]
+    fn overlay_overrides_and_appends() {
+        let overlaid: Overlaid<UbuntuDistroInfo> = Overlaid::from_readers(vec![
+            ("base".to_string(), reader(BASE)),
+            (
+                "contract".to_string(),
+                reader("series,eol-esm,eol\nfocal,2032-04-30,\n"),
+            ),
+            (
+                "internal".to_string(),
+                reader(
+                    "series,version,codename,created,release,eol\n\
+                     acme,21.04.9,Acme Anvil,2021-05-01,2021-06-01,2024-06-01\n",
+                ),
+            ),
+        ])
+        .unwrap();
+        let releases: Vec<_> = overlaid.distro_info().iter().collect();
+        assert_eq!(3, releases.len());
+        let focal = releases[0];
+        // New releases are placed by their release date, not appended
+        assert_eq!(
+            vec!["focal", "acme", "jammy"],
+            releases
+                .iter()
+                .map(|distro_release| distro_release.series().as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(&Some(naive_date(2032, 4, 30)), focal.eol_esm());
+        // An empty overlay field leaves the base value in place
+        assert_eq!(&Some(naive_date(2025, 5, 29)), focal.eol());
+        assert_eq!(&Some("21.04.9".to_string()), releases[1].version());
+
+        assert_eq!(vec!["base", "contract", "internal"], overlaid.sources());
+        assert_eq!(Some("contract"), overlaid.source_of("focal", "eol-esm"));
+        assert_eq!(Some("base"), overlaid.source_of("focal", "eol"));
+        assert_eq!(Some("base"), overlaid.source_of("jammy", "eol"));
+        assert_eq!(Some("internal"), overlaid.source_of("acme", "codename"));
+        assert_eq!(None, overlaid.source_of("jammy", "eol-esm"));
+    }
+
+    #[test]
+    fn overlay_release_order() {
+        let overlaid: Overlaid<UbuntuDistroInfo> = Overlaid::from_readers(vec![
+            ("base".to_string(), reader(BASE)),
+            (
+                "internal".to_string(),
+                reader(
+                    "series,version,codename,created,release,eol\n\
+                     acme,21.04.9,Acme Anvil,2021-05-01,2021-06-01,2030-06-01\n\
+                     zeta,26.04.9,Zeta Zebra,2026-05-01,2026-06-01,2030-06-01\n",
+                ),
+            ),
+        ])
+        .unwrap();
+        let distro_info = overlaid.distro_info();
+        // An older internal release doesn't become the latest (i.e. `--stable`) one
+        assert_eq!(
+            "jammy",
+            distro_info.latest(naive_date(2025, 1, 1)).unwrap().series()
+        );
+        assert_eq!(
+            "zeta",
+            distro_info.latest(naive_date(2026, 7, 1)).unwrap().series()
+        );
+
+        let overlaid: Overlaid<DebianDistroInfo> = Overlaid::from_readers(vec![
+            (
+                "base".to_string(),
+                reader(
+                    "version,codename,series,created,release,eol\n\
+                     12,Bookworm,bookworm,2021-08-14,2023-06-10,2026-06-10\n\
+                     ,Sid,sid,1993-08-16\n",
+                ),
+            ),
+            (
+                "internal".to_string(),
+                reader(
+                    "series,version,codename,created,release\n\
+                     acme,13,Acme,2023-06-10,2025-01-01\n",
+                ),
+            ),
+        ])
+        .unwrap();
+        // Unversioned releases stay at the end
+        assert_eq!(
+            vec!["bookworm", "acme", "sid"],
+            overlaid
+                .distro_info()
+                .iter()
+                .map(|distro_release| distro_release.series().as_str())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_overlay() {
+        let records = super::toml_records(
+            "contract.toml",
+            "[[release]]\nseries = \"focal\"\neol-esm = 2032-04-30\n\n\
+             [[release]]\nseries = \"acme\"\nversion = \"21.04.9\"\ncodename = \"Acme Anvil\"\n\
+             created = 2021-05-01\nrelease = 2021-06-01\neol = 2024-06-01\n",
+        )
+        .unwrap();
+        let overlaid: Overlaid<UbuntuDistroInfo> = Overlaid::merge(vec![
+            (
+                "base".to_string(),
+                super::csv_records("base", reader(BASE)).unwrap(),
+            ),
+            ("contract.toml".to_string(), records),
+        ])
+        .unwrap();
+        let releases: Vec<_> = overlaid.distro_info().iter().collect();
+        assert_eq!(&Some(naive_date(2032, 4, 30)), releases[0].eol_esm());
+        assert_eq!("acme", releases[1].series());
+        assert_eq!(
+            Some("contract.toml"),
+            overlaid.source_of("focal", "eol-esm")
+        );
+        assert!(super::toml_records("bad.toml", "[[release]]\neol = 2030-01-01\n").is_err());
+        assert!(super::toml_records("bad.toml", "[[release]]\nseries = 1.5\n").is_err());
+        assert!(super::toml_records("bad.toml", "release = \"focal\"\n").is_err());
+    }
+
+    #[test]
+    fn overlay_errors() {
+        // No series column
+        assert!(Overlaid::<UbuntuDistroInfo>::from_readers(vec![
+            ("base".to_string(), reader(BASE)),
+            ("bad".to_string(), reader("codename,eol\nFoo,2030-01-01\n")),
+        ])
+        .is_err());
+        // A new release missing required fields
+        assert!(Overlaid::<UbuntuDistroInfo>::from_readers(vec![
+            ("base".to_string(), reader(BASE)),
+            ("bad".to_string(), reader("series,eol\nacme,2030-01-01\n")),
+        ])
+        .is_err());
+        // An invalid date
+        assert!(Overlaid::<UbuntuDistroInfo>::from_readers(vec![
+            ("base".to_string(), reader(BASE)),
+            ("bad".to_string(), reader("series,eol\nfocal,soon\n")),
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn overlay_load_system_data() {
+        let overlaid: Overlaid<UbuntuDistroInfo> =
+            Overlaid::load(UbuntuDistroInfo::csv_path(), &[] as &[&str]).unwrap();
+        assert_eq!(
+            UbuntuDistroInfo::new().unwrap().iter().count(),
+            overlaid.distro_info().iter().count()
+        );
+    }
+}
@@ -0,0 +1,292 @@
+//! Determine the release a package was built for from its version string.
+//!
+//! Stable updates, security updates and backports conventionally carry a suffix naming the release
+//! they target:
+//!
+//! * Debian: `+deb12u3` or `~deb11u1` (stable/security updates), `+squeeze1` (older
+//!   stable/security updates) and `~bpo12+1` or `~bpo70+1` (backports)
+//! * Ubuntu: `ubuntu0.22.04.1`, `~ubuntu22.04.1` or `~22.04.1` after an Ubuntu revision such as
+//!   `0ubuntu1` (stable/security updates) and `~bpo24.04.1` (backports)
+//!
+//! Where a version has several such suffixes (e.g. a backport of a stable update), the last one
+//! wins.  The version number in a suffix is resolved to a release using the loaded data.
+use anyhow::Error;
+
+use crate::{Distro, DistroInfo, DistroRelease};
+
+/// The archive pocket a package version was built for
+///
+/// Stable and security updates use the same version suffixes, so they can't be told apart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pocket {
+    Updates,
+    Backports,
+}
+
+impl Pocket {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Pocket::Updates => "updates",
+            Pocket::Backports => "backports",
+        }
+    }
+}
+
+/// The release and pocket a package version was built for
+#[derive(Clone, Debug, PartialEq)]
+pub struct PackageTarget<'a> {
+    pub distro_release: &'a DistroRelease,
+    pub pocket: Pocket,
+}
+
+/// How a suffix identifies its release
+enum Key {
+    Version(String),
+    Series(String),
+}
+
+struct Marker {
+    position: usize,
+    pocket: Pocket,
+    key: Key,
+}
+
+fn digits_at(value: &str, start: usize) -> &str {
+    let rest = &value[start..];
+    let end = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    &rest[..end]
+}
+
+/// Returns the Ubuntu version number (e.g. `22.04`) at `start` in `value`, if there is one
+fn ubuntu_version_at(value: &str, start: usize) -> Option<String> {
+    let year = digits_at(value, start);
+    if year.is_empty() || year.len() > 2 || !value[start + year.len()..].starts_with('.') {
+        return None;
+    }
+    let month = digits_at(value, start + year.len() + 1);
+    if month.len() != 2 {
+        return None;
+    }
+    Some(format!("{}.{}", year, month))
+}
+
+/// Returns the Debian major version in a `bpo` or `deb` suffix, which was written as e.g. `60`
+/// for 6.0 before Debian 7
+fn debian_major(digits: &str) -> String {
+    match digits {
+        "40" | "50" | "60" | "70" => digits[..1].to_string(),
+        _ => digits.to_string(),
+    }
+}
+
+fn debian_markers(distro_info: &impl DistroInfo, version: &str) -> Vec<Marker> {
+    let mut markers = vec![];
+    for (position, _) in version.match_indices("bpo") {
+        let digits = digits_at(version, position + 3);
+        if !digits.is_empty() {
+            markers.push(Marker {
+                position,
+                pocket: Pocket::Backports,
+                key: Key::Version(debian_major(digits)),
+            });
+        }
+    }
+    for (position, _) in version.match_indices("deb") {
+        let digits = digits_at(version, position + 3);
+        let preceded = position > 0 && version[..position].ends_with(['+', '~']);
+        let followed = version[position + 3 + digits.len()..].starts_with('u');
+        if preceded && !digits.is_empty() && followed {
+            markers.push(Marker {
+                position,
+                pocket: Pocket::Updates,
+                key: Key::Version(debian_major(digits)),
+            });
+        }
+    }
+    // Before Debian 7, updates were suffixed with the series, e.g. `+squeeze1`
+    for distro_release in distro_info.iter() {
+        if distro_release.version().is_none() {
+            continue;
+        }
+        let series = distro_release.series();
+        for (position, _) in version.match_indices(series.as_str()) {
+            let preceded = position > 0 && version[..position].ends_with(['+', '~']);
+            if preceded && !digits_at(version, position + series.len()).is_empty() {
+                markers.push(Marker {
+                    position,
+                    pocket: Pocket::Updates,
+                    key: Key::Series(series.to_string()),
+                });
+            }
+        }
+    }
+    markers
+}
+
+/// Whether the `~` at `position` in `version` follows an Ubuntu revision such as `0ubuntu1` or
+/// `build1` in the Debian revision, so isn't an upstream pre-release tilde (e.g. `1.0~rc1-1`)
+fn follows_ubuntu_revision(version: &str, position: usize) -> bool {
+    let revision_start = match version.rfind('-') {
+        Some(hyphen) => hyphen + 1,
+        None => return false,
+    };
+    if position < revision_start {
+        return false;
+    }
+    let revision = &version[revision_start..position];
+    let name = revision.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    name.len() < revision.len() && (name.ends_with("ubuntu") || name.ends_with("build"))
+}
+
+fn ubuntu_markers(version: &str) -> Vec<Marker> {
+    let mut markers = vec![];
+    let patterns = [
+        ("ubuntu0.", Pocket::Updates),
+        ("~bpo", Pocket::Backports),
+        ("~ubuntu", Pocket::Updates),
+        ("~", Pocket::Updates),
+    ];
+    for (pattern, pocket) in patterns {
+        for (position, _) in version.match_indices(pattern) {
+            if pattern == "~" && !follows_ubuntu_revision(version, position) {
+                continue;
+            }
+            if let Some(number) = ubuntu_version_at(version, position + pattern.len()) {
+                markers.push(Marker {
+                    position,
+                    pocket,
+                    key: Key::Version(number),
+                });
+            }
+        }
+    }
+    markers
+}
+
+/// Returns the release (and pocket) the package `version` was built for, or `None` if its version
+/// doesn't name a release
+///
+/// An error is returned if the version names a release which isn't in the data.
+pub fn package_target<'a>(
+    distro_info: &'a impl DistroInfo,
+    version: &str,
+) -> Result<Option<PackageTarget<'a>>, Error> {
+    let markers = match distro_info.distro() {
+        Distro::Debian => debian_markers(distro_info, version),
+        Distro::Ubuntu => ubuntu_markers(version),
+    };
+    let marker = match markers.into_iter().max_by_key(|marker| marker.position) {
+        Some(marker) => marker,
+        None => return Ok(None),
+    };
+    let distro_release = match &marker.key {
+        Key::Series(series) => distro_info
+            .iter()
+            .find(|distro_release| distro_release.series() == series),
+        Key::Version(number) => distro_info.iter().find(|distro_release| {
+            distro_release
+                .version()
+                .as_ref()
+                .map(|release_version| match distro_info.distro() {
+                    Distro::Debian => release_version.split('.').next() == Some(number),
+                    Distro::Ubuntu => release_version.trim_end_matches(" LTS") == number,
+                })
+                .unwrap_or(false)
+        }),
+    };
+    let distro_release = distro_release.ok_or_else(|| {
+        format_err!(
+            "unknown {} release `{}' in package version `{}'",
+            distro_info.distro().to_string(),
+            match &marker.key {
+                Key::Series(series) => series,
+                Key::Version(number) => number,
+            },
+            version
+        )
+    })?;
+    Ok(Some(PackageTarget {
+        distro_release,
+        pocket: marker.pocket,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{package_target, Pocket};
+    use crate::{DebianDistroInfo, DistroInfo, UbuntuDistroInfo};
+
+    fn target(distro_info: &impl DistroInfo, version: &str) -> Option<(String, Pocket)> {
+        package_target(distro_info, version)
+            .unwrap()
+            .map(|target| (target.distro_release.series().to_string(), target.pocket))
+    }
+
+    #[test]
+    fn debian_package_versions() {
+        let debian_distro_info = DebianDistroInfo::new().unwrap();
+        let check = |version: &str, series: &str, pocket: Pocket| {
+            assert_eq!(
+                Some((series.to_string(), pocket)),
+                target(&debian_distro_info, version),
+                "{}",
+                version
+            );
+        };
+        check("2.36.1-8+deb12u3", "bookworm", Pocket::Updates);
+        check("1:9.16.48-1~deb11u1", "bullseye", Pocket::Updates);
+        check("2.2.10-1+deb10u1", "buster", Pocket::Updates);
+        check("1.0-1+deb60u2", "squeeze", Pocket::Updates);
+        check("1.0-1+squeeze1", "squeeze", Pocket::Updates);
+        check("6.10.6-1~bpo12+1", "bookworm", Pocket::Backports);
+        check("1.0-1~bpo70+1", "wheezy", Pocket::Backports);
+        // The last suffix wins
+        check("2.0-1+deb12u1~bpo11+1", "bullseye", Pocket::Backports);
+
+        assert_eq!(None, target(&debian_distro_info, "1.0-1"));
+        assert_eq!(None, target(&debian_distro_info, "1.0+debian-1"));
+        assert!(package_target(&debian_distro_info, "1.0-1+deb99u1").is_err());
+    }
+
+    #[test]
+    fn ubuntu_package_versions() {
+        let ubuntu_distro_info = UbuntuDistroInfo::new().unwrap();
+        let check = |version: &str, series: &str, pocket: Pocket| {
+            assert_eq!(
+                Some((series.to_string(), pocket)),
+                target(&ubuntu_distro_info, version),
+                "{}",
+                version
+            );
+        };
+        check("1.0-1ubuntu0.22.04.1", "jammy", Pocket::Updates);
+        check("2.0-0ubuntu1~22.04.1", "jammy", Pocket::Updates);
+        check("2.0-0ubuntu1~ubuntu20.04.2", "focal", Pocket::Updates);
+        check("3.1-1~bpo24.04.1", "noble", Pocket::Backports);
+        check("1.2-3ubuntu0.6.06", "dapper", Pocket::Updates);
+        check("2.0-0ubuntu0.1~22.04.1", "jammy", Pocket::Updates);
+        check("2.0-1build1~24.04", "noble", Pocket::Updates);
+
+        assert_eq!(None, target(&ubuntu_distro_info, "2.35-0ubuntu3.1"));
+        assert_eq!(None, target(&ubuntu_distro_info, "1.0~rc1-1"));
+        // Upstream pre-release tildes aren't release suffixes, even when they look like one
+        for version in [
+            "1.0~5.10-1",
+            "2.4~12.10-0ubuntu1",
+            "1.0~99.04-1",
+            "1.0-1~22.04",
+        ] {
+            assert_eq!(
+                None,
+                package_target(&ubuntu_distro_info, version)
+                    .unwrap()
+                    .map(|target| target.distro_release.series().to_string()),
+                "{}",
+                version
+            );
+        }
+        assert!(package_target(&ubuntu_distro_info, "1.0-1ubuntu0.99.04.1").is_err());
+    }
+}
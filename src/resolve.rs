@@ -0,0 +1,278 @@
+//! Resolve the ways people refer to a release to the release itself.
+//!
+//! A release can be named by its series (`jammy`), its codename (`Jammy Jellyfish`), its version
+//! with or without the LTS suffix (`22.04`, `22.04 LTS`, `12`), a point release of that version
+//! (`22.04.3`, `12.5`), or an alias for whichever release currently plays a role (`stable`,
+//! `devel`, ...).  Series, codenames and aliases are matched case-insensitively.
+use std::fmt;
+
+use anyhow::Error;
+use chrono::NaiveDate;
+
+use crate::suggest::UnknownRelease;
+use crate::{Distro, DistroInfo, DistroRelease, Selector};
+
+/// How an identifier matched a release
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatchKind {
+    Series,
+    Codename,
+    Version,
+    /// A point release of the version, e.g. `12.5` of `12`
+    PointRelease,
+    Alias,
+}
+
+impl fmt::Display for MatchKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MatchKind::Series => "series",
+            MatchKind::Codename => "codename",
+            MatchKind::Version => "version",
+            MatchKind::PointRelease => "point release",
+            MatchKind::Alias => "alias",
+        })
+    }
+}
+
+/// A release matched by an identifier
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Match<'a> {
+    pub distro_release: &'a DistroRelease,
+    pub kind: MatchKind,
+}
+
+/// The aliases understood for each distro, with the selector each one stands for
+fn aliases(distro: &Distro) -> &'static [(&'static str, Selector)] {
+    match distro {
+        Distro::Ubuntu => &[
+            ("devel", Selector::Devel),
+            ("latest", Selector::Latest),
+            ("lts", Selector::Lts),
+            ("stable", Selector::Stable),
+        ],
+        Distro::Debian => &[
+            ("devel", Selector::Devel),
+            ("oldstable", Selector::Oldstable),
+            ("stable", Selector::Stable),
+            ("testing", Selector::Testing),
+            ("unstable", Selector::Devel),
+        ],
+    }
+}
+
+/// `version` without Ubuntu's ` LTS` suffix
+fn bare_version(version: &str) -> &str {
+    version.trim_end_matches(" LTS")
+}
+
+/// Add `distro_release` to `matches` unless it has already matched
+fn add<'a>(matches: &mut Vec<Match<'a>>, distro_release: &'a DistroRelease, kind: MatchKind) {
+    if !matches
+        .iter()
+        .any(|existing| std::ptr::eq(existing.distro_release, distro_release))
+    {
+        matches.push(Match {
+            distro_release,
+            kind,
+        });
+    }
+}
+
+/// Returns every release that `identifier` could refer to at `date`, in release order
+///
+/// A release is listed once, with the first of series, codename, version, point release and alias
+/// that it matched by.  Point releases are only considered if no version matches exactly, so that
+/// e.g. Debian's `3.1` is sarge rather than a point release of woody's `3.0`.
+pub fn candidates<'a>(
+    distro_info: &'a impl DistroInfo,
+    identifier: &str,
+    date: NaiveDate,
+) -> Vec<Match<'a>> {
+    let needle = identifier.trim();
+    let lowered = needle.to_lowercase();
+    let mut matches: Vec<Match<'a>> = vec![];
+    for distro_release in distro_info.iter() {
+        if distro_release.series().to_lowercase() == lowered {
+            add(&mut matches, distro_release, MatchKind::Series);
+        } else if distro_release.codename().to_lowercase() == lowered {
+            add(&mut matches, distro_release, MatchKind::Codename);
+        } else if let Some(version) = distro_release.version() {
+            if version.to_lowercase() == lowered || bare_version(version) == needle {
+                add(&mut matches, distro_release, MatchKind::Version);
+            }
+        }
+    }
+    if !matches.iter().any(|m| m.kind == MatchKind::Version)
+        && needle.contains('.')
+        && needle.chars().all(|c| c.is_ascii_digit() || c == '.')
+    {
+        // The longest version that the point release extends, e.g. `6.0` rather than `6` of `6.0.10`
+        let point_release_of = distro_info
+            .iter()
+            .filter_map(|distro_release| {
+                let version = bare_version(distro_release.version().as_deref()?);
+                needle
+                    .strip_prefix(version)
+                    .filter(|rest| rest.starts_with('.'))
+                    .map(|_| (version.len(), distro_release))
+            })
+            .max_by_key(|(length, _)| *length);
+        if let Some((_, distro_release)) = point_release_of {
+            add(&mut matches, distro_release, MatchKind::PointRelease);
+        }
+    }
+    if let Some((_, selector)) = aliases(distro_info.distro())
+        .iter()
+        .find(|(alias, _)| *alias == lowered)
+    {
+        // A selector that fails (e.g. `--lts` without any LTS release) matches nothing
+        let selected = distro_info.select(selector, date).unwrap_or_default();
+        if let Some(distro_release) = selected.last() {
+            add(&mut matches, distro_release, MatchKind::Alias);
+        }
+    }
+    let position = |m: &Match| {
+        distro_info
+            .iter()
+            .position(|distro_release| std::ptr::eq(distro_release, m.distro_release))
+    };
+    matches.sort_by_key(position);
+    matches
+}
+
+/// Returns the single release that `identifier` refers to at `date`
+///
+/// It is an error for `identifier` to match no release, or to match more than one; the latter
+/// error lists each candidate and how it matched.
+pub fn resolve<'a>(
+    distro_info: &'a impl DistroInfo,
+    identifier: &str,
+    date: NaiveDate,
+) -> Result<&'a DistroRelease, Error> {
+    let matches = candidates(distro_info, identifier, date);
+    match matches.as_slice() {
+        [] => {
+            if aliases(distro_info.distro())
+                .iter()
+                .any(|(alias, _)| *alias == identifier.trim().to_lowercase())
+            {
+                bail!("no release is `{}' on {}", identifier, date);
+            }
+            bail!(UnknownRelease::new(distro_info, "release", identifier))
+        }
+        [only] => Ok(only.distro_release),
+        _ => {
+            let described: Vec<String> = matches
+                .iter()
+                .map(|m| format!("{} ({})", m.distro_release.series(), m.kind))
+                .collect();
+            bail!(
+                "ambiguous distribution release `{}' could be any of: {}",
+                identifier,
+                described.join(", ")
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{candidates, resolve, MatchKind};
+    use crate::{DebianDistroInfo, DistroInfo, UbuntuDistroInfo};
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 6, 1).unwrap()
+    }
+
+    fn resolved<T: DistroInfo>(distro_info: &T, identifier: &str) -> String {
+        resolve(distro_info, identifier, date())
+            .map(|distro_release| distro_release.series().to_string())
+            .unwrap_or_else(|e| e.to_string())
+    }
+
+    #[test]
+    fn ubuntu_identifiers() {
+        let ubuntu = UbuntuDistroInfo::new().unwrap();
+        for identifier in [
+            "jammy",
+            "Jammy",
+            "Jammy Jellyfish",
+            "jammy jellyfish",
+            "22.04",
+            "22.04 LTS",
+            "22.04.3",
+        ] {
+            assert_eq!("jammy", resolved(&ubuntu, identifier), "{}", identifier);
+        }
+        assert_eq!("noble", resolved(&ubuntu, "stable"));
+        assert_eq!("noble", resolved(&ubuntu, "LTS"));
+        assert_eq!("oracular", resolved(&ubuntu, "devel"));
+        assert_eq!("warty", resolved(&ubuntu, "4.10"));
+        assert_eq!(
+            "unknown distribution release `jammy jelly'",
+            resolved(&ubuntu, "jammy jelly")
+        );
+        assert_eq!(
+            "unknown distribution release `testing'; did you mean `questing'?",
+            resolved(&ubuntu, "testing")
+        );
+    }
+
+    #[test]
+    fn debian_identifiers() {
+        let debian = DebianDistroInfo::new().unwrap();
+        for identifier in ["bookworm", "Bookworm", "12", "12.5", "stable"] {
+            assert_eq!("bookworm", resolved(&debian, identifier), "{}", identifier);
+        }
+        assert_eq!("bullseye", resolved(&debian, "oldstable"));
+        assert_eq!("trixie", resolved(&debian, "testing"));
+        assert_eq!("sid", resolved(&debian, "unstable"));
+        assert_eq!("sid", resolved(&debian, "devel"));
+        assert_eq!("sarge", resolved(&debian, "3.1"));
+        assert_eq!("sarge", resolved(&debian, "3.1.8"));
+        assert_eq!("squeeze", resolved(&debian, "6.0.10"));
+        assert_eq!(
+            "unknown distribution release `4.1'; did you mean `buzz' (1.1), `slink' (2.1) or \
+             `sarge' (3.1)?",
+            resolved(&debian, "4.1")
+        );
+        assert_eq!(
+            vec![MatchKind::Alias],
+            candidates(&debian, "testing", date())
+                .iter()
+                .map(|m| m.kind)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn debian_testing_without_release_date() {
+        let debian = DebianDistroInfo::new().unwrap();
+        let date = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        assert_eq!("forky", resolve(&debian, "testing", date).unwrap().series());
+        assert_eq!("sid", resolve(&debian, "unstable", date).unwrap().series());
+    }
+
+    #[test]
+    fn ambiguous_and_missing() {
+        let data = "version,codename,series,created,release,eol
+1.0,Devel,stable,2020-01-01,2020-06-01,2030-01-01
+2.0,Second,second,2021-01-01,2021-06-01,2031-01-01
+";
+        let distro_info =
+            UbuntuDistroInfo::from_csv_reader(csv::Reader::from_reader(data.as_bytes())).unwrap();
+        assert_eq!(
+            "ambiguous distribution release `stable' could be any of: stable (series), \
+             second (alias)",
+            resolved(&distro_info, "stable")
+        );
+        assert_eq!("stable", resolved(&distro_info, "Devel"));
+        assert_eq!(
+            "no release is `lts' on 2024-06-01",
+            resolved(&distro_info, "lts")
+        );
+    }
+}
@@ -0,0 +1,208 @@
+//! Summarise release cadence and support durations.
+//!
+//! Each statistic summarises the lengths, in days, of one kind of interval across a set of
+//! releases; releases lacking either end of an interval are left out of it.  For Ubuntu, each
+//! statistic is also broken down into LTS and interim releases.
+use chrono::NaiveDate;
+
+use crate::{Distro, DistroRelease};
+
+/// A kind of interval in the release lifecycle
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interval {
+    /// From the opening of development to release
+    CreatedToRelease,
+    /// The length of standard support
+    ReleaseToEol,
+    /// From the end of standard support to the end of LTS
+    EolToEolLTS,
+    /// From the end of standard support to the end of ELTS
+    EolToEolELTS,
+    /// From the end of standard support to the end of ESM
+    EolToEolESM,
+    /// From each release to the next (of the same category)
+    ReleaseGap,
+}
+
+impl Interval {
+    pub const ALL: [Interval; 6] = [
+        Interval::CreatedToRelease,
+        Interval::ReleaseToEol,
+        Interval::EolToEolLTS,
+        Interval::EolToEolELTS,
+        Interval::EolToEolESM,
+        Interval::ReleaseGap,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Interval::CreatedToRelease => "created-to-release",
+            Interval::ReleaseToEol => "release-to-eol",
+            Interval::EolToEolLTS => "eol-to-eol-lts",
+            Interval::EolToEolELTS => "eol-to-eol-elts",
+            Interval::EolToEolESM => "eol-to-eol-esm",
+            Interval::ReleaseGap => "release-gap",
+        }
+    }
+
+    /// The start and end dates of this interval for `distro_release`, if it has both
+    fn dates(&self, distro_release: &DistroRelease) -> Option<(NaiveDate, NaiveDate)> {
+        let (start, end) = match self {
+            Interval::CreatedToRelease => (distro_release.created(), distro_release.release()),
+            Interval::ReleaseToEol => (distro_release.release(), distro_release.eol()),
+            Interval::EolToEolLTS => (distro_release.eol(), distro_release.eol_lts()),
+            Interval::EolToEolELTS => (distro_release.eol(), distro_release.eol_elts()),
+            Interval::EolToEolESM => (distro_release.eol(), distro_release.eol_esm()),
+            Interval::ReleaseGap => return None,
+        };
+        Some(((*start)?, (*end)?))
+    }
+}
+
+/// Summary statistics of a set of durations, in days
+#[derive(Clone, Debug, PartialEq)]
+pub struct Summary {
+    pub count: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub min: i64,
+    pub max: i64,
+}
+
+impl Summary {
+    /// Summarise `days`, if there are any
+    pub fn of(days: &[i64]) -> Option<Self> {
+        if days.is_empty() {
+            return None;
+        }
+        let mut sorted = days.to_vec();
+        sorted.sort_unstable();
+        let count = sorted.len();
+        let median = if count % 2 == 0 {
+            (sorted[count / 2 - 1] + sorted[count / 2]) as f64 / 2.0
+        } else {
+            sorted[count / 2] as f64
+        };
+        Some(Self {
+            count,
+            mean: sorted.iter().sum::<i64>() as f64 / count as f64,
+            median,
+            min: sorted[0],
+            max: sorted[count - 1],
+        })
+    }
+}
+
+/// A summary of one kind of interval across one category of releases
+#[derive(Clone, Debug, PartialEq)]
+pub struct Statistic {
+    pub interval: Interval,
+    /// `all`, or `LTS` or `interim` for Ubuntu
+    pub category: &'static str,
+    pub summary: Summary,
+}
+
+fn durations(interval: Interval, distro_releases: &[&DistroRelease]) -> Vec<i64> {
+    let days = |(start, end): (NaiveDate, NaiveDate)| end.signed_duration_since(start).num_days();
+    match interval {
+        Interval::ReleaseGap => {
+            let mut releases: Vec<NaiveDate> = distro_releases
+                .iter()
+                .filter_map(|distro_release| *distro_release.release())
+                .collect();
+            releases.sort_unstable();
+            releases
+                .windows(2)
+                .map(|pair| days((pair[0], pair[1])))
+                .collect()
+        }
+        _ => distro_releases
+            .iter()
+            .filter_map(|distro_release| interval.dates(distro_release))
+            .map(days)
+            .collect(),
+    }
+}
+
+/// Returns statistics for every interval that occurs in `distro_releases`, of the given distro
+pub fn statistics(distro: &Distro, distro_releases: &[&DistroRelease]) -> Vec<Statistic> {
+    let mut categories: Vec<(&'static str, Vec<&DistroRelease>)> =
+        vec![("all", distro_releases.to_vec())];
+    if let Distro::Ubuntu = distro {
+        let (lts, interim) = distro_releases
+            .iter()
+            .partition(|distro_release| distro_release.ubuntu_is_lts());
+        categories.push(("LTS", lts));
+        categories.push(("interim", interim));
+    }
+    let mut statistics = vec![];
+    for interval in Interval::ALL {
+        for (category, distro_releases) in categories.iter() {
+            if let Some(summary) = Summary::of(&durations(interval, distro_releases)) {
+                statistics.push(Statistic {
+                    interval,
+                    category,
+                    summary,
+                });
+            }
+        }
+    }
+    statistics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{statistics, Interval, Summary};
+    use crate::{Distro, DistroInfo, UbuntuDistroInfo};
+
+    #[test]
+    fn summary_of() {
+        assert_eq!(None, Summary::of(&[]));
+        let summary = Summary::of(&[10, 2, 6]).unwrap();
+        assert_eq!((3, 6.0, 6.0, 2, 10), summary_tuple(&summary));
+        let summary = Summary::of(&[4, 1, 3, 2]).unwrap();
+        assert_eq!((4, 2.5, 2.5, 1, 4), summary_tuple(&summary));
+    }
+
+    fn summary_tuple(summary: &Summary) -> (usize, f64, f64, i64, i64) {
+        (
+            summary.count,
+            summary.mean,
+            summary.median,
+            summary.min,
+            summary.max,
+        )
+    }
+
+    #[test]
+    fn ubuntu_statistics() {
+        let ubuntu_distro_info = UbuntuDistroInfo::new().unwrap();
+        let distro_releases: Vec<_> = ubuntu_distro_info
+            .iter()
+            .filter(|distro_release| {
+                ["trusty", "utopic", "vivid", "wily", "xenial"]
+                    .contains(&distro_release.series().as_str())
+            })
+            .collect();
+        let statistics = statistics(&Distro::Ubuntu, &distro_releases);
+        let find = |interval: Interval, category: &str| {
+            statistics
+                .iter()
+                .find(|statistic| statistic.interval == interval && statistic.category == category)
+                .map(|statistic| statistic.summary.clone())
+        };
+        let gaps = find(Interval::ReleaseGap, "all").unwrap();
+        assert_eq!(4, gaps.count);
+        // trusty and xenial were released two years apart
+        let lts_gaps = find(Interval::ReleaseGap, "LTS").unwrap();
+        assert_eq!(1, lts_gaps.count);
+        assert_eq!(735, lts_gaps.min);
+        // Interim releases were supported for nine months
+        let interim = find(Interval::ReleaseToEol, "interim").unwrap();
+        assert_eq!(3, interim.count);
+        assert!(interim.min > 250 && interim.max < 300);
+        // Only the LTS releases have ESM
+        assert_eq!(2, find(Interval::EolToEolESM, "all").unwrap().count);
+        assert_eq!(None, find(Interval::EolToEolESM, "interim"));
+    }
+}
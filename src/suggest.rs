@@ -0,0 +1,165 @@
+//! "Did you mean" suggestions for release identifiers that match nothing.
+//!
+//! Functions that look releases up report a miss with an [`UnknownRelease`] error, which can be
+//! recovered from their `anyhow::Error` with `downcast_ref`.
+use std::fmt;
+
+use crate::{DistroInfo, DistroRelease};
+
+/// The most suggestions to offer for an unknown identifier
+const MAX_SUGGESTIONS: usize = 3;
+
+/// A known release near to an unknown identifier
+#[derive(Clone, Debug, PartialEq)]
+pub struct Suggestion {
+    pub series: String,
+    /// The series, codename or version that was near the identifier
+    pub matched: String,
+}
+
+impl fmt::Display for Suggestion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}'", self.series)?;
+        if self.matched != self.series {
+            write!(f, " ({})", self.matched)?;
+        }
+        Ok(())
+    }
+}
+
+/// An identifier that no release has, with the known releases nearest to it
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnknownRelease {
+    /// What the identifier was expected to be: "series" or "release"
+    pub kind: &'static str,
+    pub identifier: String,
+    pub suggestions: Vec<Suggestion>,
+}
+
+impl UnknownRelease {
+    pub fn new(distro_info: &impl DistroInfo, kind: &'static str, identifier: &str) -> Self {
+        Self {
+            kind,
+            identifier: identifier.to_string(),
+            suggestions: suggestions(distro_info, identifier),
+        }
+    }
+}
+
+impl fmt::Display for UnknownRelease {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown distribution {} `{}'",
+            self.kind, self.identifier
+        )?;
+        if let Some((last, rest)) = self.suggestions.split_last() {
+            write!(f, "; did you mean ")?;
+            if !rest.is_empty() {
+                let rest: Vec<String> = rest.iter().map(Suggestion::to_string).collect();
+                write!(f, "{} or ", rest.join(", "))?;
+            }
+            write!(f, "{}?", last)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for UnknownRelease {}
+
+/// The names a release can be looked up by, series first
+fn names(distro_release: &DistroRelease) -> Vec<&str> {
+    let mut names = vec![
+        distro_release.series().as_str(),
+        distro_release.codename().as_str(),
+    ];
+    if let Some(version) = distro_release.version() {
+        names.push(version);
+        names.push(version.trim_end_matches(" LTS"));
+    }
+    names
+}
+
+/// Returns the releases whose series, codename or version is within a small edit distance of
+/// `identifier`, nearest first
+///
+/// The allowed distance grows with the length of `identifier` (one edit per three characters, and
+/// at least one), so that short identifiers aren't matched to everything.
+pub fn suggestions(distro_info: &impl DistroInfo, identifier: &str) -> Vec<Suggestion> {
+    let needle = identifier.trim().to_lowercase();
+    let max_distance = (needle.chars().count() / 3).max(1);
+    let mut nearest: Vec<(usize, Suggestion)> = distro_info
+        .iter()
+        .filter_map(|distro_release| {
+            names(distro_release)
+                .into_iter()
+                .map(|name| {
+                    let distance = strsim::damerau_levenshtein(&needle, &name.to_lowercase());
+                    (distance, name)
+                })
+                .filter(|(distance, _)| *distance <= max_distance)
+                .min_by_key(|(distance, _)| *distance)
+                .map(|(distance, name)| {
+                    (
+                        distance,
+                        Suggestion {
+                            series: distro_release.series().to_string(),
+                            matched: name.to_string(),
+                        },
+                    )
+                })
+        })
+        .collect();
+    // A stable sort keeps equally near releases in release order
+    nearest.sort_by_key(|(distance, _)| *distance);
+    nearest
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, suggestion)| suggestion)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{suggestions, UnknownRelease};
+    use crate::{DebianDistroInfo, DistroInfo, UbuntuDistroInfo};
+
+    fn suggested<T: DistroInfo>(distro_info: &T, identifier: &str) -> Vec<String> {
+        suggestions(distro_info, identifier)
+            .iter()
+            .map(|suggestion| suggestion.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn near_misses() {
+        let ubuntu = UbuntuDistroInfo::new().unwrap();
+        assert_eq!(vec!["`jammy'"], suggested(&ubuntu, "jamy"));
+        assert_eq!(
+            vec!["`jammy' (Jammy Jellyfish)"],
+            suggested(&ubuntu, "Jammy Jelyfish")
+        );
+        assert_eq!(vec!["`jammy' (22.04)"], suggested(&ubuntu, "22.4"));
+        assert!(suggested(&ubuntu, "bookworm").is_empty());
+        let debian = DebianDistroInfo::new().unwrap();
+        assert_eq!(vec!["`bookworm'"], suggested(&debian, "bookwrom"));
+    }
+
+    #[test]
+    fn unknown_release_message() {
+        let ubuntu = UbuntuDistroInfo::new().unwrap();
+        assert_eq!(
+            "unknown distribution series `jamy'; did you mean `jammy'?",
+            UnknownRelease::new(&ubuntu, "series", "jamy").to_string()
+        );
+        assert_eq!(
+            "unknown distribution series `zzzzzzzz'",
+            UnknownRelease::new(&ubuntu, "series", "zzzzzzzz").to_string()
+        );
+        let error: anyhow::Error = UnknownRelease::new(&ubuntu, "series", "jamy").into();
+        assert_eq!(
+            "jammy",
+            error.downcast_ref::<UnknownRelease>().unwrap().suggestions[0].series
+        );
+    }
+}
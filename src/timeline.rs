@@ -0,0 +1,410 @@
+//! Render the support lifecycles of a set of releases as a timeline, in one of several formats.
+//!
+//! Each release is drawn as a bar running from its release date to its final EOL milestone, split
+//! into segments by [`Phase`](../enum.Phase.html).  An optional marker date (typically the date
+//! passed to `--date`) is drawn across all bars.
+use std::fmt::Write;
+use std::str::FromStr;
+
+use anyhow::Error;
+use chrono::{Datelike, NaiveDate};
+
+use crate::{DistroRelease, Phase};
+
+const ASCII_WIDTH: i64 = 60;
+const SVG_LABEL_WIDTH: i64 = 100;
+const SVG_CHART_WIDTH: i64 = 800;
+const SVG_ROW_HEIGHT: i64 = 20;
+const SVG_HEADER_HEIGHT: i64 = 40;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TimelineFormat {
+    Ascii,
+    Svg,
+    Mermaid,
+}
+
+impl FromStr for TimelineFormat {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Error> {
+        match value {
+            "ascii" => Ok(Self::Ascii),
+            "svg" => Ok(Self::Svg),
+            "mermaid" => Ok(Self::Mermaid),
+            _ => bail!(
+                "unknown timeline format `{}'; must be one of ascii, svg, mermaid",
+                value
+            ),
+        }
+    }
+}
+
+/// Render a timeline titled `title` for `distro_releases` in the given format
+pub fn render(
+    format: &TimelineFormat,
+    title: &str,
+    distro_releases: &[&DistroRelease],
+    marker: Option<NaiveDate>,
+) -> String {
+    match format {
+        TimelineFormat::Ascii => render_ascii(title, distro_releases, marker),
+        TimelineFormat::Svg => render_svg(title, distro_releases, marker),
+        TimelineFormat::Mermaid => render_mermaid(title, distro_releases, marker),
+    }
+}
+
+/// The earliest and latest dates that need to be drawn
+fn date_range(
+    distro_releases: &[&DistroRelease],
+    marker: Option<NaiveDate>,
+) -> Option<(NaiveDate, NaiveDate)> {
+    let dates: Vec<NaiveDate> = distro_releases
+        .iter()
+        .flat_map(|distro_release| distro_release.support_segments())
+        .flat_map(|segment| [segment.start, segment.end])
+        .chain(marker)
+        .collect();
+    Some((*dates.iter().min()?, *dates.iter().max()?))
+}
+
+/// Scale `date` to a position between 0 and `width` within `range`
+fn scale(date: NaiveDate, (start, end): (NaiveDate, NaiveDate), width: i64) -> i64 {
+    let span = end.signed_duration_since(start).num_days().max(1);
+    date.signed_duration_since(start).num_days() * width / span
+}
+
+fn ascii_char(phase: &Phase) -> char {
+    match phase {
+        Phase::Standard => '#',
+        Phase::LTS => '=',
+        Phase::ELTS => '-',
+        Phase::ESM => '.',
+    }
+}
+
+fn render_ascii(
+    title: &str,
+    distro_releases: &[&DistroRelease],
+    marker: Option<NaiveDate>,
+) -> String {
+    let mut out = String::new();
+    writeln!(out, "{}", title).unwrap();
+    let label_width = distro_releases
+        .iter()
+        .map(|distro_release| distro_release.series().len())
+        .max()
+        .unwrap_or(0);
+    let range = date_range(distro_releases, marker);
+    if let Some((start, end)) = range {
+        let start_year = start.year().to_string();
+        writeln!(
+            out,
+            "{:label_width$} {}{:>pad$}",
+            "",
+            start_year,
+            end.year(),
+            pad = ASCII_WIDTH as usize - start_year.len(),
+        )
+        .unwrap();
+    }
+    for distro_release in distro_releases {
+        let mut bar = vec![' '; ASCII_WIDTH as usize];
+        if let Some(range) = range {
+            let column = |date| scale(date, range, ASCII_WIDTH - 1) as usize;
+            for segment in distro_release.support_segments() {
+                for cell in &mut bar[column(segment.start)..=column(segment.end)] {
+                    *cell = ascii_char(&segment.phase);
+                }
+            }
+            if let Some(marker) = marker {
+                bar[column(marker)] = '|';
+            }
+        }
+        let bar: String = bar.into_iter().collect();
+        writeln!(
+            out,
+            "{:label_width$} {}",
+            distro_release.series(),
+            bar.trim_end()
+        )
+        .unwrap();
+    }
+    let mut legend: Vec<String> = [Phase::Standard, Phase::LTS, Phase::ELTS, Phase::ESM]
+        .iter()
+        .map(|phase| format!("{} {}", ascii_char(phase), phase.name()))
+        .collect();
+    if let Some(marker) = marker {
+        legend.push(format!("| {}", marker));
+    }
+    writeln!(out, "{:label_width$} {}", "", legend.join("  ")).unwrap();
+    out
+}
+
+fn svg_colour(phase: &Phase) -> &'static str {
+    match phase {
+        Phase::Standard => "#4caf50",
+        Phase::LTS => "#2196f3",
+        Phase::ELTS => "#9c27b0",
+        Phase::ESM => "#ff9800",
+    }
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render_svg(
+    title: &str,
+    distro_releases: &[&DistroRelease],
+    marker: Option<NaiveDate>,
+) -> String {
+    let width = SVG_LABEL_WIDTH + SVG_CHART_WIDTH + SVG_ROW_HEIGHT;
+    let chart_bottom = SVG_HEADER_HEIGHT + SVG_ROW_HEIGHT * distro_releases.len() as i64;
+    let height = chart_bottom + SVG_ROW_HEIGHT * 2;
+    let mut out = String::new();
+    writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         viewBox=\"0 0 {width} {height}\" font-family=\"sans-serif\" font-size=\"12\">"
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "<text x=\"0\" y=\"15\" font-size=\"14\" font-weight=\"bold\">{}</text>",
+        escape_xml(title)
+    )
+    .unwrap();
+    let range = date_range(distro_releases, marker);
+    let x = |date| SVG_LABEL_WIDTH + scale(date, range.unwrap(), SVG_CHART_WIDTH);
+    if let Some((start, end)) = range {
+        let years = end.year() - start.year();
+        let step = if years > 20 { 5 } else { 1 };
+        for year in (start.year() + 1..=end.year()).filter(|year| year % step == 0) {
+            let year_x = x(NaiveDate::from_ymd_opt(year, 1, 1).unwrap());
+            writeln!(
+                out,
+                "<line x1=\"{year_x}\" y1=\"{}\" x2=\"{year_x}\" y2=\"{chart_bottom}\" \
+                 stroke=\"#dddddd\"/>",
+                SVG_HEADER_HEIGHT - 5
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "<text x=\"{year_x}\" y=\"{}\" text-anchor=\"middle\">{year}</text>",
+                SVG_HEADER_HEIGHT - 8
+            )
+            .unwrap();
+        }
+    }
+    for (idx, distro_release) in distro_releases.iter().enumerate() {
+        let y = SVG_HEADER_HEIGHT + SVG_ROW_HEIGHT * idx as i64;
+        writeln!(
+            out,
+            "<text x=\"0\" y=\"{}\">{}</text>",
+            y + SVG_ROW_HEIGHT - 6,
+            escape_xml(distro_release.series())
+        )
+        .unwrap();
+        for segment in distro_release.support_segments() {
+            let start_x = x(segment.start);
+            writeln!(
+                out,
+                "<rect x=\"{start_x}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\">\
+                 <title>{} {}: {} to {}</title></rect>",
+                y + 2,
+                (x(segment.end) - start_x).max(1),
+                SVG_ROW_HEIGHT - 4,
+                svg_colour(&segment.phase),
+                escape_xml(distro_release.series()),
+                segment.phase.name(),
+                segment.start,
+                segment.end,
+            )
+            .unwrap();
+        }
+    }
+    if let Some(marker) = marker {
+        let marker_x = x(marker);
+        writeln!(
+            out,
+            "<line x1=\"{marker_x}\" y1=\"{}\" x2=\"{marker_x}\" y2=\"{chart_bottom}\" \
+             stroke=\"#f44336\" stroke-width=\"2\"><title>{marker}</title></line>",
+            SVG_HEADER_HEIGHT - 5
+        )
+        .unwrap();
+    }
+    let legend_y = chart_bottom + SVG_ROW_HEIGHT;
+    for (idx, phase) in [Phase::Standard, Phase::LTS, Phase::ELTS, Phase::ESM]
+        .iter()
+        .enumerate()
+    {
+        let legend_x = SVG_LABEL_WIDTH + 100 * idx as i64;
+        writeln!(
+            out,
+            "<rect x=\"{legend_x}\" y=\"{}\" width=\"12\" height=\"12\" fill=\"{}\"/>\
+             <text x=\"{}\" y=\"{legend_y}\">{}</text>",
+            legend_y - 10,
+            svg_colour(phase),
+            legend_x + 16,
+            phase.name()
+        )
+        .unwrap();
+    }
+    writeln!(out, "</svg>").unwrap();
+    out
+}
+
+fn render_mermaid(
+    title: &str,
+    distro_releases: &[&DistroRelease],
+    marker: Option<NaiveDate>,
+) -> String {
+    let mut out = String::new();
+    writeln!(out, "gantt").unwrap();
+    writeln!(out, "    title {}", title).unwrap();
+    writeln!(out, "    dateFormat YYYY-MM-DD").unwrap();
+    writeln!(out, "    axisFormat %Y").unwrap();
+    for distro_release in distro_releases {
+        let segments = distro_release.support_segments();
+        if segments.is_empty() {
+            continue;
+        }
+        writeln!(out, "    section {}", distro_release.series()).unwrap();
+        for segment in segments {
+            let tag = match segment.phase {
+                Phase::Standard => "active, ",
+                Phase::LTS | Phase::ELTS => "",
+                Phase::ESM => "done, ",
+            };
+            writeln!(
+                out,
+                "    {} :{}{}_{}, {}, {}",
+                segment.phase.name(),
+                tag,
+                distro_release.series(),
+                segment.phase.name().to_lowercase(),
+                segment.start,
+                segment.end
+            )
+            .unwrap();
+        }
+    }
+    if let Some(marker) = marker {
+        writeln!(out, "    section Date").unwrap();
+        writeln!(out, "    {} :milestone, marker, {}, 0d", marker, marker).unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render, TimelineFormat};
+    use crate::tests::naive_date;
+    use crate::DistroRelease;
+
+    fn distro_releases() -> Vec<DistroRelease> {
+        vec![
+            DistroRelease::new(
+                "10".to_string(),
+                "Buster".to_string(),
+                "buster".to_string(),
+                Some(naive_date(2017, 6, 17)),
+                Some(naive_date(2019, 7, 6)),
+                Some(naive_date(2022, 9, 10)),
+                Some(naive_date(2024, 6, 30)),
+                Some(naive_date(2029, 6, 30)),
+                None,
+                None,
+            ),
+            DistroRelease::new(
+                "".to_string(),
+                "Sid".to_string(),
+                "sid".to_string(),
+                Some(naive_date(1993, 8, 16)),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            ),
+        ]
+    }
+
+    #[test]
+    fn timeline_format_from_str() {
+        assert_eq!(TimelineFormat::Ascii, "ascii".parse().unwrap());
+        assert_eq!(TimelineFormat::Svg, "svg".parse().unwrap());
+        assert_eq!(TimelineFormat::Mermaid, "mermaid".parse().unwrap());
+        assert!("png".parse::<TimelineFormat>().is_err());
+    }
+
+    #[test]
+    fn timeline_ascii() {
+        let distro_releases = distro_releases();
+        let distro_releases: Vec<_> = distro_releases.iter().collect();
+        let rendered = render(
+            &TimelineFormat::Ascii,
+            "Debian",
+            &distro_releases,
+            Some(naive_date(2023, 1, 1)),
+        );
+        let lines: Vec<_> = rendered.lines().collect();
+        assert_eq!("Debian", lines[0]);
+        assert!(lines[1].trim_start().starts_with("2019"));
+        assert!(lines[1].ends_with("2029"));
+        assert!(lines[2].starts_with("buster #"));
+        assert!(lines[2].contains("|="));
+        assert!(lines[2].ends_with('-'));
+        assert_eq!("sid", lines[3].split('|').next().unwrap().trim_end());
+        assert!(lines[4].ends_with("| 2023-01-01"));
+    }
+
+    #[test]
+    fn timeline_svg() {
+        let distro_releases = distro_releases();
+        let distro_releases: Vec<_> = distro_releases.iter().collect();
+        let rendered = render(
+            &TimelineFormat::Svg,
+            "Debian <all>",
+            &distro_releases,
+            Some(naive_date(2023, 1, 1)),
+        );
+        assert!(rendered.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(rendered.trim_end().ends_with("</svg>"));
+        assert!(rendered.contains("Debian &lt;all&gt;"));
+        assert!(rendered.contains("<title>buster ELTS: 2024-06-30 to 2029-06-30</title>"));
+        assert!(rendered.contains("<title>2023-01-01</title>"));
+    }
+
+    #[test]
+    fn timeline_mermaid() {
+        let distro_releases = distro_releases();
+        let distro_releases: Vec<_> = distro_releases.iter().collect();
+        let rendered = render(
+            &TimelineFormat::Mermaid,
+            "Debian",
+            &distro_releases,
+            Some(naive_date(2023, 1, 1)),
+        );
+        assert_eq!(
+            "gantt
+    title Debian
+    dateFormat YYYY-MM-DD
+    axisFormat %Y
+    section buster
+    standard :active, buster_standard, 2019-07-06, 2022-09-10
+    LTS :buster_lts, 2022-09-10, 2024-06-30
+    ELTS :buster_elts, 2024-06-30, 2029-06-30
+    section Date
+    2023-01-01 :milestone, marker, 2023-01-01, 0d
+",
+            rendered
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! Compute the supported upgrade hops between two releases.
+//!
+//! Ubuntu supports upgrading an interim release to the next release, and an LTS release to the
+//! next release or the next LTS; Debian supports upgrading each stable release to the next.
+use anyhow::Error;
+use chrono::NaiveDate;
+
+use crate::suggest::UnknownRelease;
+use crate::{Distro, DistroInfo, DistroRelease, Milestone};
+
+/// A release passed through on the way to an upgrade's target
+#[derive(Clone, Debug, PartialEq)]
+pub struct UpgradeStep<'a> {
+    pub distro_release: &'a DistroRelease,
+    /// Whether the release was released but no longer supported at the date of the query
+    pub eol: bool,
+}
+
+/// Returns the releases to upgrade through, in order, to get from the release with series `from`
+/// to the one with series `to`
+///
+/// The path ends with `to`, and uses LTS-to-LTS upgrades for Ubuntu wherever the target allows.
+pub fn upgrade_path<'a>(
+    distro_info: &'a impl DistroInfo,
+    from: &str,
+    to: &str,
+    date: NaiveDate,
+) -> Result<Vec<UpgradeStep<'a>>, Error> {
+    let releases: Vec<&DistroRelease> = distro_info.iter().collect();
+    let position = |series: &str| {
+        releases
+            .iter()
+            .position(|distro_release| distro_release.series() == series)
+            .ok_or_else(|| UnknownRelease::new(distro_info, "series", series))
+    };
+    let (from_index, to_index) = (position(from)?, position(to)?);
+    if to_index <= from_index {
+        bail!("`{}' is not newer than `{}'", to, from);
+    }
+    // The first release after `current` satisfying `predicate`, or the target if there is none;
+    // releases which haven't been created at `date` are only stepped through if they're the target
+    let next = |current: usize, predicate: &dyn Fn(&DistroRelease) -> bool| {
+        (current + 1..to_index)
+            .find(|&index| releases[index].created_at(date) && predicate(releases[index]))
+            .unwrap_or(to_index)
+    };
+    let mut indices = vec![];
+    let mut current = from_index;
+    while current != to_index {
+        current = match distro_info.distro() {
+            Distro::Ubuntu if releases[current].ubuntu_is_lts() => {
+                match next(current, &|distro_release| distro_release.ubuntu_is_lts()) {
+                    // Without an LTS to jump to, step to the next release as interims do
+                    index if index == to_index && !releases[to_index].ubuntu_is_lts() => {
+                        next(current, &|_| true)
+                    }
+                    index => index,
+                }
+            }
+            Distro::Ubuntu => next(current, &|_| true),
+            // Skip unversioned releases (i.e. sid and experimental) unless they're the target
+            Distro::Debian => next(current, &|distro_release| {
+                distro_release.version().is_some()
+            }),
+        };
+        indices.push(current);
+    }
+    Ok(indices
+        .into_iter()
+        .map(|index| {
+            let distro_release = releases[index];
+            let supported = match distro_info.distro() {
+                Distro::Ubuntu => distro_release.ubuntu_supported_at(date),
+                Distro::Debian => distro_release.supported_at(date, &Milestone::Eol),
+            };
+            UpgradeStep {
+                distro_release,
+                eol: distro_release.released_at(date) && !supported,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::upgrade_path;
+    use crate::tests::naive_date;
+    use crate::{DebianDistroInfo, DistroInfo, UbuntuDistroInfo};
+
+    fn path(
+        distro_info: &impl DistroInfo,
+        from: &str,
+        to: &str,
+        date: (i32, u32, u32),
+    ) -> Vec<String> {
+        upgrade_path(distro_info, from, to, naive_date(date.0, date.1, date.2))
+            .unwrap()
+            .into_iter()
+            .map(|step| {
+                let series = step.distro_release.series();
+                if step.eol {
+                    format!("{} (EOL)", series)
+                } else {
+                    series.to_string()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ubuntu_upgrade_paths() {
+        let ubuntu_distro_info = UbuntuDistroInfo::new().unwrap();
+        assert_eq!(
+            vec!["focal", "jammy", "noble"],
+            path(&ubuntu_distro_info, "bionic", "noble", (2024, 6, 1))
+        );
+        // Interim targets are reached through the preceding LTS
+        assert_eq!(
+            vec!["focal", "jammy", "kinetic (EOL)", "lunar", "mantic"],
+            path(&ubuntu_distro_info, "bionic", "mantic", (2023, 11, 1))
+        );
+        // Interim releases upgrade one release at a time
+        assert_eq!(
+            vec!["mantic (EOL)", "noble"],
+            path(&ubuntu_distro_info, "lunar", "noble", (2024, 9, 1))
+        );
+        assert_eq!(
+            vec!["noble"],
+            path(&ubuntu_distro_info, "jammy", "noble", (2024, 9, 1))
+        );
+    }
+
+    #[test]
+    fn debian_upgrade_paths() {
+        let debian_distro_info = DebianDistroInfo::new().unwrap();
+        assert_eq!(
+            vec!["stretch (EOL)", "buster (EOL)", "bullseye", "bookworm"],
+            path(&debian_distro_info, "jessie", "bookworm", (2024, 6, 1))
+        );
+        assert_eq!(
+            vec!["trixie", "forky", "sid"],
+            path(&debian_distro_info, "bookworm", "sid", (2025, 9, 1))
+        );
+        // Releases yet to be created are only reached as the target
+        assert_eq!(
+            vec!["trixie", "duke"],
+            path(&debian_distro_info, "bookworm", "duke", (2025, 1, 1))
+        );
+    }
+
+    #[test]
+    fn upgrade_path_errors() {
+        let ubuntu_distro_info = UbuntuDistroInfo::new().unwrap();
+        let date = naive_date(2024, 9, 1);
+        assert!(upgrade_path(&ubuntu_distro_info, "noble", "jammy", date).is_err());
+        assert!(upgrade_path(&ubuntu_distro_info, "noble", "noble", date).is_err());
+        assert!(upgrade_path(&ubuntu_distro_info, "bionic", "nonexistent", date).is_err());
+    }
+}